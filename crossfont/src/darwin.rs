@@ -0,0 +1,658 @@
+//! macOS rasterizer backend, built on CoreText/CoreGraphics.
+
+use std::collections::HashMap;
+use std::iter;
+use std::path::PathBuf;
+use std::ptr;
+
+use core_foundation::array::{CFArray, CFIndex};
+use core_foundation::base::{CFType, ItemRef, TCFType};
+use core_foundation::dictionary::CFDictionary;
+use core_foundation::number::{CFNumber, CFNumberRef};
+use core_foundation::string::CFString;
+use core_graphics::base::kCGImageAlphaPremultipliedFirst;
+use core_graphics::color_space::CGColorSpace;
+use core_graphics::context::CGContext;
+use core_graphics::font::CGGlyph;
+use core_graphics::geometry::{CGPoint, CGRect, CGSize};
+use core_text::font::{
+    cascade_list_for_languages as ct_cascade_list_for_languages, new_from_descriptor,
+    new_from_name, CTFont,
+};
+use core_text::font_collection::create_for_family;
+use core_text::font_descriptor::{
+    self, kCTFontColorGlyphsTrait, kCTFontDefaultOrientation, kCTFontEnabledAttribute,
+    kCTFontVariationAttribute, CTFontDescriptor, SymbolicTraitAccessors,
+};
+
+use log::{trace, warn};
+
+use crate::{
+    BitmapBuffer, Error, FontDesc, FontKey, FontRenderMode, FontVariation, GlyphDimensions,
+    GlyphKey, Metrics, RasterizedGlyph, Rasterize, Size, Slant, Style, Weight, MISSING_GLYPH_INDEX,
+};
+
+/// `kCGBitmapByteOrder32Host`, which the `core-graphics` crate pinned here doesn't expose.
+#[allow(non_upper_case_globals)]
+const kCGBitmapByteOrder32Host: u32 = 8192;
+
+pub struct Rasterizer {
+    fonts: HashMap<FontKey, Font>,
+    keys: HashMap<(FontDesc, Size), FontKey>,
+    device_pixel_ratio: f32,
+    render_mode: FontRenderMode,
+    /// Ordered BCP-47 language tags used to build each font's fallback cascade list. Defaults to
+    /// English; set via `set_fallback_languages` so CJK/RTL users get script-appropriate
+    /// fallbacks instead of CoreText's English-biased default ordering.
+    fallback_languages: Vec<String>,
+    /// Resolved fallback cascade, keyed by (family, scaled size bits, languages), so repeated
+    /// `load_font` calls for different styles of the same family don't re-walk CoreText's cascade
+    /// list each time.
+    fallback_cache: HashMap<(String, u64, Vec<String>), Vec<Font>>,
+}
+
+impl Rasterize for Rasterizer {
+    fn new(device_pixel_ratio: f32) -> Rasterizer {
+        Rasterizer {
+            fonts: HashMap::new(),
+            keys: HashMap::new(),
+            device_pixel_ratio,
+            render_mode: FontRenderMode::GrayscaleAa,
+            fallback_languages: vec!["en".to_owned()],
+            fallback_cache: HashMap::new(),
+        }
+    }
+
+    /// Get metrics for font specified by FontKey.
+    fn metrics(&self, key: FontKey, _size: Size) -> Result<Metrics, Error> {
+        let font = self.fonts.get(&key).ok_or(Error::UnknownFontKey)?;
+        font.metrics()
+    }
+
+    fn load_font(&mut self, desc: &FontDesc, size: Size) -> Result<FontKey, Error> {
+        let scaled_size = Size::new(size.as_f32_pts() * self.device_pixel_ratio);
+        self.keys.get(&(desc.to_owned(), scaled_size)).map(|k| Ok(*k)).unwrap_or_else(|| {
+            let font = self.get_font(desc, size)?;
+            let key = FontKey::next();
+
+            self.fonts.insert(key, font);
+            self.keys.insert((desc.clone(), scaled_size), key);
+
+            Ok(key)
+        })
+    }
+
+    /// Get rasterized glyph for given glyph key.
+    fn get_glyph(&mut self, glyph: GlyphKey) -> Result<RasterizedGlyph, Error> {
+        let (font, glyph_index) = self.resolve_glyph(glyph)?;
+
+        let rasterized = font.get_glyph(glyph.character, glyph_index, self.render_mode);
+
+        if glyph_index == MISSING_GLYPH_INDEX {
+            Err(Error::MissingGlyph(rasterized))
+        } else {
+            Ok(rasterized)
+        }
+    }
+
+    /// Measure the glyph described by `glyph` without drawing a bitmap for it.
+    fn glyph_dimensions(&mut self, glyph: GlyphKey) -> Result<GlyphDimensions, Error> {
+        let (font, glyph_index) = self.resolve_glyph(glyph)?;
+
+        if glyph_index == MISSING_GLYPH_INDEX {
+            return Err(Error::MissingGlyph(RasterizedGlyph::default()));
+        }
+
+        Ok(font.glyph_dimensions(glyph_index))
+    }
+
+    fn update_dpr(&mut self, device_pixel_ratio: f32) {
+        self.device_pixel_ratio = device_pixel_ratio;
+    }
+
+    fn set_render_mode(&mut self, render_mode: FontRenderMode) {
+        self.render_mode = render_mode;
+    }
+}
+
+impl Rasterizer {
+    /// Set the ordered BCP-47 language tags used to build fallback cascade lists for faces loaded
+    /// after this call. Cached fallbacks already resolved under a different language list are
+    /// left in place, since another caller may still want them.
+    pub fn set_fallback_languages(&mut self, languages: Vec<String>) {
+        self.fallback_languages = languages;
+    }
+
+    /// Resolve (or look up in `fallback_cache`) the fallback cascade for `ct_font`, a face in
+    /// `family` at `size`.
+    fn resolve_fallbacks(&mut self, ct_font: &CTFont, family: &str, size: f64) -> Vec<Font> {
+        let key = (family.to_owned(), size.to_bits(), self.fallback_languages.clone());
+        if let Some(fallbacks) = self.fallback_cache.get(&key) {
+            return fallbacks.clone();
+        }
+
+        let mut fallbacks = cascade_list_for_languages(ct_font, &self.fallback_languages)
+            .into_iter()
+            .filter(|desc| !desc.font_path.as_os_str().is_empty())
+            .map(|desc| desc.to_font(size, &[]))
+            .collect::<Vec<_>>();
+
+        // TODO, we can't use apple's proposed
+        // .Apple Symbol Fallback (filtered out below),
+        // but not having these makes us not able to render
+        // many chars. We add the symbols back in.
+        // Investigate if we can actually use the .-prefixed
+        // fallbacks somehow.
+        if let Ok(apple_symbols) = new_from_name("Apple Symbols", size) {
+            fallbacks.push(Font { ct_font: apple_symbols, fallbacks: Vec::new() })
+        };
+
+        self.fallback_cache.insert(key, fallbacks.clone());
+        fallbacks
+    }
+
+    /// Resolve a `GlyphKey` to its loaded font and a glyph index in it, searching the font's
+    /// fallback cascade if the character isn't directly present. Returns
+    /// `MISSING_GLYPH_INDEX` (with the primary font) rather than an error when no font in the
+    /// cascade has the glyph, since callers differ in how they want to handle that (draw
+    /// `.notdef`, report `MissingGlyph`, etc).
+    fn resolve_glyph(&self, glyph: GlyphKey) -> Result<(&Font, u32), Error> {
+        let font = self.fonts.get(&glyph.font_key).ok_or(Error::UnknownFontKey)?;
+
+        let resolved = iter::once(font)
+            .chain(font.fallbacks.iter())
+            .find_map(|font| match font.glyph_index(glyph.character) {
+                MISSING_GLYPH_INDEX => None,
+                glyph_index => Some((font, glyph_index)),
+            })
+            .unwrap_or((font, MISSING_GLYPH_INDEX));
+
+        Ok(resolved)
+    }
+
+    fn get_specific_face(
+        &mut self,
+        desc: &FontDesc,
+        style: &str,
+        size: Size,
+    ) -> Result<Font, Error> {
+        let descriptors = descriptors_for_family(&desc.name[..]);
+        for descriptor in descriptors {
+            if descriptor.style_name == style {
+                // Found the font we want.
+                let scaled_size = f64::from(size.as_f32_pts()) * f64::from(self.device_pixel_ratio);
+                let mut font = descriptor.to_font(scaled_size, &desc.variations);
+                font.fallbacks = self.resolve_fallbacks(&font.ct_font, &desc.name, scaled_size);
+                return Ok(font);
+            }
+        }
+
+        Err(Error::FontNotFound(desc.to_owned()))
+    }
+
+    fn get_matching_face(
+        &mut self,
+        desc: &FontDesc,
+        slant: Slant,
+        weight: Weight,
+        size: Size,
+    ) -> Result<Font, Error> {
+        let bold = weight == Weight::Bold;
+        let italic = slant != Slant::Normal;
+        let scaled_size = f64::from(size.as_f32_pts()) * f64::from(self.device_pixel_ratio);
+
+        let descriptors = descriptors_for_family(&desc.name[..]);
+        for descriptor in descriptors {
+            let font = descriptor.to_font(scaled_size, &desc.variations);
+            if font.is_bold() == bold && font.is_italic() == italic {
+                // Found the font we want.
+                let mut font = font;
+                font.fallbacks = self.resolve_fallbacks(&font.ct_font, &desc.name, scaled_size);
+                return Ok(font);
+            }
+        }
+
+        Err(Error::FontNotFound(desc.to_owned()))
+    }
+
+    fn get_font(&mut self, desc: &FontDesc, size: Size) -> Result<Font, Error> {
+        match desc.style {
+            Style::Specific(ref style) => self.get_specific_face(desc, style, size),
+            Style::Description { slant, weight } => {
+                self.get_matching_face(desc, slant, weight, size)
+            },
+        }
+    }
+}
+
+/// A font.
+#[derive(Clone)]
+struct Font {
+    ct_font: CTFont,
+    fallbacks: Vec<Font>,
+}
+
+unsafe impl Send for Font {}
+
+impl Font {
+    fn metrics(&self) -> Result<Metrics, Error> {
+        let average_advance = self.glyph_advance('0');
+
+        let ascent = self.ct_font.ascent().round() as f64;
+        let descent = self.ct_font.descent().round() as f64;
+        let leading = self.ct_font.leading().round() as f64;
+        let line_height = ascent + descent + leading;
+
+        if line_height == 0.0 {
+            // A font CoreText failed to size properly; surfacing bogus zero-height metrics would
+            // silently collapse the grid instead of telling the caller something is wrong.
+            return Err(Error::MetricsNotFound);
+        }
+
+        // Strikeout and underline metrics.
+        // CoreText doesn't provide strikeout so we provide our own.
+        let underline_position = self.ct_font.underline_position() as f32;
+        let underline_thickness = self.ct_font.underline_thickness() as f32;
+        let strikeout_position = (line_height / 2. - descent) as f32;
+        let strikeout_thickness = underline_thickness;
+
+        Ok(Metrics {
+            average_advance,
+            line_height,
+            descent: -(descent as f32),
+            underline_position,
+            underline_thickness,
+            strikeout_position,
+            strikeout_thickness,
+        })
+    }
+
+    fn is_bold(&self) -> bool {
+        self.ct_font.symbolic_traits().is_bold()
+    }
+
+    fn is_italic(&self) -> bool {
+        self.ct_font.symbolic_traits().is_italic()
+    }
+
+    fn is_colored(&self) -> bool {
+        (self.ct_font.symbolic_traits() & kCTFontColorGlyphsTrait) != 0
+    }
+
+    fn glyph_advance(&self, character: char) -> f64 {
+        let index = self.glyph_index(character);
+
+        let indices = [index as CGGlyph];
+
+        unsafe {
+            self.ct_font.get_advances_for_glyphs(
+                kCTFontDefaultOrientation,
+                &indices[0],
+                ptr::null_mut(),
+                1,
+            )
+        }
+    }
+
+    /// Horizontal and vertical advance for a single already-resolved `glyph_index`, rounded to
+    /// whole device pixels, for [`RasterizedGlyph::advance`].
+    fn glyph_index_advance(&self, glyph_index: u32) -> (i32, i32) {
+        let indices = [glyph_index as CGGlyph];
+        let mut advances = [CGSize::new(0.0, 0.0)];
+
+        unsafe {
+            self.ct_font.get_advances_for_glyphs(
+                kCTFontDefaultOrientation,
+                &indices[0],
+                &mut advances[0],
+                1,
+            );
+        }
+
+        (advances[0].width.round() as i32, advances[0].height.round() as i32)
+    }
+
+    /// Width/height/top/left/advance for `glyph_index`, without drawing a bitmap for it.
+    fn glyph_dimensions(&self, glyph_index: u32) -> GlyphDimensions {
+        let bounds = self
+            .ct_font
+            .get_bounding_rects_for_glyphs(kCTFontDefaultOrientation, &[glyph_index as CGGlyph]);
+
+        let left = bounds.origin.x.floor() as i32;
+        let width = (bounds.origin.x - f64::from(left) + bounds.size.width).ceil() as i32;
+        let descent = (-bounds.origin.y).ceil() as i32;
+        let ascent = (bounds.size.height + bounds.origin.y).ceil() as i32;
+        let height = descent + ascent;
+
+        GlyphDimensions { width, height, top: ascent, left, advance: self.glyph_index_advance(glyph_index) }
+    }
+
+    fn get_glyph(
+        &self,
+        character: char,
+        glyph_index: u32,
+        render_mode: FontRenderMode,
+    ) -> RasterizedGlyph {
+        let dims = self.glyph_dimensions(glyph_index);
+        let rasterized_left = dims.left;
+        let rasterized_width = dims.width as u32;
+        let rasterized_height = dims.height as u32;
+        // `glyph_dimensions` already folds ascent + descent into `height`/`top`; recover descent
+        // for the rasterization origin below instead of re-deriving it from the bounding rect.
+        let rasterized_descent = dims.height - dims.top;
+
+        if rasterized_width == 0 || rasterized_height == 0 {
+            return RasterizedGlyph {
+                character: ' ',
+                width: 0,
+                height: 0,
+                top: 0,
+                left: 0,
+                advance: (0, 0),
+                buffer: BitmapBuffer::Rgb(Vec::new()),
+            };
+        }
+
+        let is_colored = self.is_colored();
+
+        let buffer = if is_colored {
+            // Colored (emoji) glyphs carry their own alpha; a single pass over a transparent
+            // background is enough.
+            let pixels = self.rasterize_pass(
+                glyph_index,
+                rasterized_width,
+                rasterized_height,
+                rasterized_left,
+                rasterized_descent,
+                (0.0, 0.0, 0.0, 0.0),
+                (1.0, 1.0, 1.0, 1.0),
+                false,
+            );
+            BitmapBuffer::Rgba(extract_rgba(&pixels))
+        } else if render_mode == FontRenderMode::SubpixelAa {
+            // Rasterize twice with font smoothing enabled, once over each extreme background, and
+            // diff the per-channel output to recover independent subpixel coverage. This mirrors
+            // the dual-background coverage trick WebRender's macOS rasterizer uses to synthesize
+            // component alpha.
+            let black_bg = self.rasterize_pass(
+                glyph_index,
+                rasterized_width,
+                rasterized_height,
+                rasterized_left,
+                rasterized_descent,
+                (0.0, 0.0, 0.0, 1.0),
+                (1.0, 1.0, 1.0, 1.0),
+                true,
+            );
+            let white_bg = self.rasterize_pass(
+                glyph_index,
+                rasterized_width,
+                rasterized_height,
+                rasterized_left,
+                rasterized_descent,
+                (1.0, 1.0, 1.0, 1.0),
+                (0.0, 0.0, 0.0, 1.0),
+                true,
+            );
+            BitmapBuffer::Rgb(subpixel_coverage(&white_bg, &black_bg))
+        } else {
+            let pixels = self.rasterize_pass(
+                glyph_index,
+                rasterized_width,
+                rasterized_height,
+                rasterized_left,
+                rasterized_descent,
+                (0.0, 0.0, 0.0, 1.0),
+                (1.0, 1.0, 1.0, 1.0),
+                false,
+            );
+            BitmapBuffer::Rgb(extract_rgb(&pixels))
+        };
+
+        RasterizedGlyph {
+            character,
+            left: dims.left,
+            top: dims.top,
+            width: dims.width,
+            height: dims.height,
+            advance: dims.advance,
+            buffer,
+        }
+    }
+
+    /// Draw `glyph_index` into a fresh bitmap context filled with `bg` and return the raw
+    /// premultiplied BGRA bytes. `smooth` enables CoreText's own LCD font smoothing, which is
+    /// what gives the two passes of [`Self::get_glyph`]'s subpixel trick differing per-channel
+    /// output in the first place.
+    #[allow(clippy::too_many_arguments)]
+    fn rasterize_pass(
+        &self,
+        glyph_index: u32,
+        width: u32,
+        height: u32,
+        left: i32,
+        descent: i32,
+        bg: (f64, f64, f64, f64),
+        fg: (f64, f64, f64, f64),
+        smooth: bool,
+    ) -> Vec<u8> {
+        let mut cg_context = CGContext::create_bitmap_context(
+            None,
+            width as usize,
+            height as usize,
+            8, // bits per component
+            width as usize * 4,
+            &CGColorSpace::create_device_rgb(),
+            kCGImageAlphaPremultipliedFirst | kCGBitmapByteOrder32Host,
+        );
+
+        cg_context.set_rgb_fill_color(bg.0, bg.1, bg.2, bg.3);
+        let context_rect =
+            CGRect::new(&CGPoint::new(0.0, 0.0), &CGSize::new(f64::from(width), f64::from(height)));
+        cg_context.fill_rect(context_rect);
+
+        cg_context.set_allows_font_smoothing(smooth);
+        cg_context.set_should_smooth_fonts(smooth);
+        cg_context.set_allows_font_subpixel_quantization(true);
+        cg_context.set_should_subpixel_quantize_fonts(true);
+        cg_context.set_allows_font_subpixel_positioning(true);
+        cg_context.set_should_subpixel_position_fonts(true);
+        cg_context.set_allows_antialiasing(true);
+        cg_context.set_should_antialias(true);
+
+        cg_context.set_rgb_fill_color(fg.0, fg.1, fg.2, fg.3);
+        let rasterization_origin = CGPoint { x: f64::from(-left), y: f64::from(descent) };
+
+        self.ct_font.draw_glyphs(&[glyph_index as CGGlyph], &[rasterization_origin], cg_context.clone());
+
+        cg_context.data().to_vec()
+    }
+
+    fn glyph_index(&self, character: char) -> u32 {
+        // Encode this char as utf-16.
+        let mut buffer = [0; 2];
+        let encoded: &[u16] = character.encode_utf16(&mut buffer);
+        // And use the utf-16 buffer to get the index.
+        self.glyph_index_utf16(encoded)
+    }
+
+    fn glyph_index_utf16(&self, encoded: &[u16]) -> u32 {
+        // Output buffer for the glyph. for non-BMP glyphs, like
+        // emojis, this will be filled with two chars the second
+        // always being a 0.
+        let mut glyphs: [CGGlyph; 2] = [0; 2];
+
+        let res = unsafe {
+            self.ct_font.get_glyphs_for_characters(
+                encoded.as_ptr(),
+                glyphs.as_mut_ptr(),
+                encoded.len() as CFIndex,
+            )
+        };
+
+        if res {
+            u32::from(glyphs[0])
+        } else {
+            MISSING_GLYPH_INDEX
+        }
+    }
+}
+
+/// Font descriptor.
+///
+/// The descriptor provides data about a font and supports creating a font.
+#[derive(Debug)]
+struct Descriptor {
+    style_name: String,
+    font_path: PathBuf,
+
+    ct_descriptor: CTFontDescriptor,
+}
+
+impl Descriptor {
+    fn new(desc: CTFontDescriptor) -> Descriptor {
+        Descriptor {
+            style_name: desc.style_name(),
+            font_path: desc.font_path().unwrap_or_else(PathBuf::new),
+            ct_descriptor: desc,
+        }
+    }
+
+    /// Create a `Font` from this descriptor, applying `variations` (OpenType variation-axis
+    /// settings, e.g. `wght`/`opsz`) on top of it first if the face supports them. The fallback
+    /// cascade is left empty; callers resolve it separately via `Rasterizer::resolve_fallbacks`,
+    /// which caches the result instead of re-walking CoreText's cascade list on every call.
+    fn to_font(&self, size: f64, variations: &[FontVariation]) -> Font {
+        let ct_descriptor = if variations.is_empty() {
+            self.ct_descriptor.clone()
+        } else {
+            descriptor_with_variations(&self.ct_descriptor, variations)
+        };
+        let ct_font = new_from_descriptor(&ct_descriptor, size);
+
+        Font { ct_font, fallbacks: Vec::new() }
+    }
+}
+
+/// Copy `descriptor` with a `kCTFontVariationAttribute` dictionary merged in, mapping each
+/// variation's 4-byte OpenType tag (packed big-endian, as the `fvar`/`STAT` tables do) to its
+/// requested axis value.
+fn descriptor_with_variations(
+    descriptor: &CTFontDescriptor,
+    variations: &[FontVariation],
+) -> CTFontDescriptor {
+    let pairs: Vec<(CFNumber, CFNumber)> = variations
+        .iter()
+        .map(|variation| {
+            let tag = i64::from(u32::from_be_bytes(variation.tag));
+            (CFNumber::from(tag), CFNumber::from(f64::from(variation.value)))
+        })
+        .collect();
+    let variation_dict = CFDictionary::from_CFType_pairs(&pairs);
+
+    let attrs = CFDictionary::from_CFType_pairs(&[(
+        unsafe { CFString::wrap_under_get_rule(kCTFontVariationAttribute) },
+        variation_dict.as_CFType(),
+    )]);
+
+    descriptor.create_copy_with_attributes(&attrs).unwrap_or_else(|| descriptor.clone())
+}
+
+/// Return fallback descriptors for font/language list.
+fn cascade_list_for_languages(ct_font: &CTFont, languages: &[String]) -> Vec<Descriptor> {
+    // Convert language type &Vec<String> -> CFArray.
+    let langarr: CFArray<CFString> = {
+        let tmp: Vec<CFString> = languages.iter().map(|language| CFString::new(language)).collect();
+        CFArray::from_CFTypes(&tmp)
+    };
+
+    // CFArray of CTFontDescriptorRef (again).
+    let list = ct_cascade_list_for_languages(ct_font, &langarr);
+
+    // Convert CFArray to Vec<Descriptor>.
+    list.into_iter().filter(is_enabled).map(|fontdesc| Descriptor::new(fontdesc.clone())).collect()
+}
+
+/// Check if a font is enabled.
+fn is_enabled(fontdesc: &ItemRef<'_, CTFontDescriptor>) -> bool {
+    unsafe {
+        let descriptor = fontdesc.as_concrete_TypeRef();
+        let attr_val =
+            font_descriptor::CTFontDescriptorCopyAttribute(descriptor, kCTFontEnabledAttribute);
+
+        if attr_val.is_null() {
+            return false;
+        }
+
+        let attr_val = CFType::wrap_under_create_rule(attr_val);
+        let attr_val = CFNumber::wrap_under_get_rule(attr_val.as_CFTypeRef() as CFNumberRef);
+
+        attr_val.to_i32().unwrap_or(0) != 0
+    }
+}
+
+/// Get descriptors for family name.
+fn descriptors_for_family(family: &str) -> Vec<Descriptor> {
+    let mut out = Vec::new();
+
+    trace!("Family: {}", family);
+    let ct_collection = create_for_family(family).unwrap_or_else(|| {
+        // Fallback to Menlo if we can't find the config specified font family.
+        warn!("Unable to load specified font {}, falling back to Menlo", &family);
+        create_for_family("Menlo").expect("Menlo exists")
+    });
+
+    // CFArray of CTFontDescriptorRef (i think).
+    let descriptors = ct_collection.get_descriptors();
+    if let Some(descriptors) = descriptors {
+        for descriptor in descriptors.iter() {
+            out.push(Descriptor::new(descriptor.clone()));
+        }
+    }
+
+    out
+}
+
+/// Reorder a host-byte-order premultiplied-ARGB `CGContext` buffer (in memory: B, G, R, A) into a
+/// tightly packed RGB alphamask, dropping alpha. The bytes are copied as-is, not unpremultiplied.
+fn extract_rgb(buffer: &[u8]) -> Vec<u8> {
+    let mut rgb = Vec::with_capacity(buffer.len() / 4 * 3);
+    for chunk in buffer.chunks_exact(4) {
+        let (b, g, r) = (chunk[0], chunk[1], chunk[2]);
+        rgb.push(r);
+        rgb.push(g);
+        rgb.push(b);
+    }
+    rgb
+}
+
+/// Like [`extract_rgb`], but keeps the alpha channel for colored (emoji) glyphs.
+fn extract_rgba(buffer: &[u8]) -> Vec<u8> {
+    let mut rgba = Vec::with_capacity(buffer.len());
+    for chunk in buffer.chunks_exact(4) {
+        let (b, g, r, a) = (chunk[0], chunk[1], chunk[2], chunk[3]);
+        rgba.push(r);
+        rgba.push(g);
+        rgba.push(b);
+        rgba.push(a);
+    }
+    rgba
+}
+
+/// Recover independent per-channel subpixel coverage from the white- and black-background
+/// rasterization passes of [`Font::get_glyph`]'s `FontRenderMode::SubpixelAa` path.
+///
+/// For premultiplied output, `coverage_c = 255 - (white_c - black_c)`, clamped to `0..=255`. The
+/// white pass draws black on white and the black pass white on black, so a channel untouched by
+/// the glyph reads 255 and 0 (difference 255, no coverage), and a channel fully covered by the
+/// glyph reads 0 and 255 (difference -255, clamped to full coverage).
+fn subpixel_coverage(white_bg: &[u8], black_bg: &[u8]) -> Vec<u8> {
+    let mut rgb = Vec::with_capacity(white_bg.len() / 4 * 3);
+    for (white, black) in white_bg.chunks_exact(4).zip(black_bg.chunks_exact(4)) {
+        let coverage = |w: u8, b: u8| (255 - (i32::from(w) - i32::from(b))).clamp(0, 255) as u8;
+        rgb.push(coverage(white[2], black[2])); // r
+        rgb.push(coverage(white[1], black[1])); // g
+        rgb.push(coverage(white[0], black[0])); // b
+    }
+    rgb
+}
@@ -0,0 +1,181 @@
+//! Windows rasterizer backend, built on DirectWrite.
+
+use std::collections::HashMap;
+
+use dwrote::{
+    FontCollection, FontStretch, FontStyle as DwFontStyle, FontWeight as DwFontWeight,
+    GlyphRunAnalysis, DWRITE_MEASURING_MODE_NATURAL, DWRITE_RENDERING_MODE_NATURAL,
+    DWRITE_TEXTURE_CLEARTYPE_3x1,
+};
+
+use crate::{
+    BitmapBuffer, Error, FontDesc, FontKey, GlyphDimensions, GlyphKey, Metrics, RasterizedGlyph,
+    Rasterize, Size, Slant, Style, Weight,
+};
+
+pub struct Rasterizer {
+    fonts: HashMap<FontKey, Font>,
+    keys: HashMap<(FontDesc, Size), FontKey>,
+    device_pixel_ratio: f32,
+}
+
+impl Rasterize for Rasterizer {
+    fn new(device_pixel_ratio: f32) -> Rasterizer {
+        Rasterizer { fonts: HashMap::new(), keys: HashMap::new(), device_pixel_ratio }
+    }
+
+    fn metrics(&self, key: FontKey, _size: Size) -> Result<Metrics, Error> {
+        let font = self.fonts.get(&key).ok_or(Error::UnknownFontKey)?;
+        Ok(font.metrics())
+    }
+
+    fn load_font(&mut self, desc: &FontDesc, size: Size) -> Result<FontKey, Error> {
+        let scaled_size = Size::new(size.as_f32_pts() * self.device_pixel_ratio);
+        if let Some(key) = self.keys.get(&(desc.to_owned(), scaled_size)) {
+            return Ok(*key);
+        }
+
+        let font = self.get_font(desc, scaled_size)?;
+        let key = FontKey::next();
+
+        self.fonts.insert(key, font);
+        self.keys.insert((desc.clone(), scaled_size), key);
+
+        Ok(key)
+    }
+
+    fn get_glyph(&mut self, glyph: GlyphKey) -> Result<RasterizedGlyph, Error> {
+        let font = self.fonts.get(&glyph.font_key).ok_or(Error::UnknownFontKey)?;
+        Ok(font.get_glyph(glyph.character))
+    }
+
+    fn glyph_dimensions(&mut self, glyph: GlyphKey) -> Result<GlyphDimensions, Error> {
+        let font = self.fonts.get(&glyph.font_key).ok_or(Error::UnknownFontKey)?;
+        Ok(font.glyph_dimensions(glyph.character))
+    }
+
+    fn update_dpr(&mut self, device_pixel_ratio: f32) {
+        self.device_pixel_ratio = device_pixel_ratio;
+    }
+}
+
+impl Rasterizer {
+    fn get_font(&self, desc: &FontDesc, size: Size) -> Result<Font, Error> {
+        let collection = FontCollection::system();
+        let family =
+            collection.get_font_family_by_name(&desc.name).ok_or_else(|| Error::FontNotFound(desc.to_owned()))?;
+
+        let (dw_style, dw_weight) = match &desc.style {
+            Style::Specific(_) => (DwFontStyle::Normal, DwFontWeight::Regular),
+            Style::Description { slant, weight } => (
+                match slant {
+                    Slant::Normal => DwFontStyle::Normal,
+                    Slant::Italic => DwFontStyle::Italic,
+                    Slant::Oblique => DwFontStyle::Oblique,
+                },
+                match weight {
+                    Weight::Normal => DwFontWeight::Regular,
+                    Weight::Bold => DwFontWeight::Bold,
+                },
+            ),
+        };
+
+        let dw_font = family.get_first_matching_font(dw_weight, FontStretch::Normal, dw_style);
+        let face = dw_font.create_font_face();
+
+        Ok(Font { face, size: f64::from(size.as_f32_pts()) })
+    }
+}
+
+/// A loaded DirectWrite font face at a fixed point size.
+struct Font {
+    face: dwrote::FontFace,
+    size: f64,
+}
+
+impl Font {
+    fn metrics(&self) -> Metrics {
+        let design_metrics = self.face.metrics();
+        let scale = self.size / f64::from(design_metrics.designUnitsPerEm);
+
+        let ascent = f64::from(design_metrics.ascent) * scale;
+        let descent = f64::from(design_metrics.descent) * scale;
+        let line_gap = f64::from(design_metrics.lineGap) * scale;
+
+        Metrics {
+            average_advance: self.glyph_advance(' ') as f64,
+            line_height: ascent + descent + line_gap,
+            descent: -(descent as f32),
+            underline_position: (f64::from(design_metrics.underlinePosition) * scale) as f32,
+            underline_thickness: (f64::from(design_metrics.underlineThickness) * scale) as f32,
+            strikeout_position: (f64::from(design_metrics.strikethroughPosition) * scale) as f32,
+            strikeout_thickness: (f64::from(design_metrics.strikethroughThickness) * scale) as f32,
+        }
+    }
+
+    fn glyph_advance(&self, character: char) -> i32 {
+        let glyph_indices = self.face.get_glyph_indices(&[character as u32]);
+        let metrics = self.face.get_design_glyph_metrics(&glyph_indices, false);
+        let scale = self.size / f64::from(self.face.metrics().designUnitsPerEm);
+        (f64::from(metrics[0].advanceWidth) * scale) as i32
+    }
+
+    fn get_glyph(&self, character: char) -> RasterizedGlyph {
+        let glyph_indices = self.face.get_glyph_indices(&[character as u32]);
+        let glyph_index = glyph_indices[0];
+
+        let analysis = GlyphRunAnalysis::create(
+            &self.face,
+            self.size,
+            &[glyph_index],
+            DWRITE_RENDERING_MODE_NATURAL,
+            DWRITE_MEASURING_MODE_NATURAL,
+            0.0,
+            0.0,
+        );
+
+        let bounds = analysis.get_alpha_texture_bounds(DWRITE_TEXTURE_CLEARTYPE_3x1);
+        let width = (bounds.right - bounds.left).max(0);
+        let height = (bounds.bottom - bounds.top).max(0);
+
+        let buffer = if width > 0 && height > 0 {
+            analysis.create_alpha_texture(DWRITE_TEXTURE_CLEARTYPE_3x1, bounds)
+        } else {
+            Vec::new()
+        };
+
+        RasterizedGlyph {
+            character,
+            width,
+            height,
+            top: -bounds.top,
+            left: bounds.left,
+            advance: (self.glyph_advance(character), 0),
+            buffer: BitmapBuffer::Rgb(buffer),
+        }
+    }
+
+    /// Width/height/top/left/advance for `character`, without asking DirectWrite to allocate and
+    /// rasterize its alpha texture.
+    fn glyph_dimensions(&self, character: char) -> GlyphDimensions {
+        let glyph_indices = self.face.get_glyph_indices(&[character as u32]);
+        let metrics = self.face.get_design_glyph_metrics(&glyph_indices, false)[0];
+        let scale = self.size / f64::from(self.face.metrics().designUnitsPerEm);
+
+        let width = f64::from(metrics.advanceWidth)
+            - f64::from(metrics.leftSideBearing)
+            - f64::from(metrics.rightSideBearing);
+        let height = f64::from(metrics.advanceHeight)
+            - f64::from(metrics.topSideBearing)
+            - f64::from(metrics.bottomSideBearing);
+        let top = f64::from(metrics.verticalOriginY) - f64::from(metrics.topSideBearing);
+
+        GlyphDimensions {
+            width: (width * scale) as i32,
+            height: (height * scale) as i32,
+            top: (top * scale) as i32,
+            left: (f64::from(metrics.leftSideBearing) * scale) as i32,
+            advance: (self.glyph_advance(character), 0),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Linux/BSD rasterizer backend, built on FreeType and fontconfig.
+
+use std::collections::HashMap;
+
+use freetype::face::{Face, LoadFlag};
+use freetype::Library;
+
+use fontconfig::{Fontconfig, Pattern};
+
+use crate::{
+    BitmapBuffer, Error, FontDesc, FontKey, GlyphDimensions, GlyphKey, Metrics, RasterizedGlyph,
+    Rasterize, Size, Slant, Style, Weight,
+};
+
+pub struct Rasterizer {
+    library: Library,
+    fontconfig: Fontconfig,
+    faces: HashMap<FontKey, Face>,
+    keys: HashMap<(FontDesc, Size), FontKey>,
+    device_pixel_ratio: f32,
+}
+
+impl Rasterize for Rasterizer {
+    fn new(device_pixel_ratio: f32) -> Rasterizer {
+        let library = Library::init().expect("unable to initialize FreeType");
+        let fontconfig = Fontconfig::new().expect("unable to initialize fontconfig");
+
+        Rasterizer {
+            library,
+            fontconfig,
+            faces: HashMap::new(),
+            keys: HashMap::new(),
+            device_pixel_ratio,
+        }
+    }
+
+    fn metrics(&self, key: FontKey, _size: Size) -> Result<Metrics, Error> {
+        let face = self.faces.get(&key).ok_or(Error::UnknownFontKey)?;
+        let size_metrics = face.size_metrics().ok_or(Error::MetricsNotFound)?;
+
+        Ok(Metrics {
+            average_advance: f64::from(size_metrics.max_advance >> 6),
+            line_height: f64::from(size_metrics.height >> 6),
+            descent: (size_metrics.descender >> 6) as f32,
+            underline_position: (face.underline_position() >> 6) as f32,
+            underline_thickness: (face.underline_thickness() >> 6) as f32,
+            strikeout_position: (size_metrics.ascender >> 7) as f32,
+            strikeout_thickness: (face.underline_thickness() >> 6) as f32,
+        })
+    }
+
+    fn load_font(&mut self, desc: &FontDesc, size: Size) -> Result<FontKey, Error> {
+        let scaled_size = Size::new(size.as_f32_pts() * self.device_pixel_ratio);
+        if let Some(key) = self.keys.get(&(desc.to_owned(), scaled_size)) {
+            return Ok(*key);
+        }
+
+        let path = self.path_for_desc(desc)?;
+
+        let face = self
+            .library
+            .new_face(&path, 0)
+            .map_err(|_| Error::FontNotFound(desc.to_owned()))?;
+
+        // Convert from points (26.6 fixed-point pixels) at the current device pixel ratio.
+        let pixel_size = (scaled_size.as_f32_pts() * 64.0) as isize;
+        face.set_char_size(pixel_size, 0, 0, 0).map_err(|_| Error::FontNotFound(desc.to_owned()))?;
+
+        let key = FontKey::next();
+        self.faces.insert(key, face);
+        self.keys.insert((desc.clone(), scaled_size), key);
+
+        Ok(key)
+    }
+
+    fn get_glyph(&mut self, glyph: GlyphKey) -> Result<RasterizedGlyph, Error> {
+        let face = self.faces.get(&glyph.font_key).ok_or(Error::UnknownFontKey)?;
+
+        face.load_char(glyph.character as usize, LoadFlag::RENDER | LoadFlag::COLOR)
+            .map_err(|_| Error::MissingGlyph(RasterizedGlyph::default()))?;
+
+        let glyph_slot = face.glyph();
+        let bitmap = glyph_slot.bitmap();
+
+        let buffer = if bitmap.pixel_mode() == freetype::bitmap::PixelMode::Bgra {
+            BitmapBuffer::Rgba(bgra_to_rgba(bitmap.buffer()))
+        } else {
+            // The default (grayscale) render mode yields one coverage byte per pixel; replicate it
+            // across the three channels `BitmapBuffer::Rgb` holds.
+            BitmapBuffer::Rgb(bitmap.buffer().iter().flat_map(|&coverage| [coverage; 3]).collect())
+        };
+
+        Ok(RasterizedGlyph {
+            character: glyph.character,
+            width: bitmap.width(),
+            height: bitmap.rows(),
+            top: glyph_slot.bitmap_top(),
+            left: glyph_slot.bitmap_left(),
+            advance: ((glyph_slot.advance().x >> 6) as i32, (glyph_slot.advance().y >> 6) as i32),
+            buffer,
+        })
+    }
+
+    fn glyph_dimensions(&mut self, glyph: GlyphKey) -> Result<GlyphDimensions, Error> {
+        let face = self.faces.get(&glyph.font_key).ok_or(Error::UnknownFontKey)?;
+
+        // `LoadFlag::RENDER` is the part that actually rasterizes a bitmap; loading without it
+        // still populates `glyph_slot.metrics()`.
+        face.load_char(glyph.character as usize, LoadFlag::DEFAULT)
+            .map_err(|_| Error::MissingGlyph(RasterizedGlyph::default()))?;
+
+        let metrics = face.glyph().metrics();
+
+        Ok(GlyphDimensions {
+            width: (metrics.width >> 6) as i32,
+            height: (metrics.height >> 6) as i32,
+            top: (metrics.horiBearingY >> 6) as i32,
+            left: (metrics.horiBearingX >> 6) as i32,
+            advance: ((metrics.horiAdvance >> 6) as i32, (metrics.vertAdvance >> 6) as i32),
+        })
+    }
+
+    fn update_dpr(&mut self, device_pixel_ratio: f32) {
+        self.device_pixel_ratio = device_pixel_ratio;
+    }
+}
+
+impl Rasterizer {
+    /// Use fontconfig to resolve a `FontDesc` (family + style) to a face file on disk.
+    fn path_for_desc(&self, desc: &FontDesc) -> Result<String, Error> {
+        let mut pattern = Pattern::new(&self.fontconfig);
+        pattern.add_family(&desc.name);
+
+        match &desc.style {
+            Style::Specific(style) => pattern.add_style(style),
+            Style::Description { slant, weight } => {
+                pattern.add_slant(match slant {
+                    Slant::Italic | Slant::Oblique => fontconfig::Slant::Italic,
+                    Slant::Normal => fontconfig::Slant::Roman,
+                });
+                pattern.add_weight(match weight {
+                    Weight::Bold => fontconfig::Weight::Bold,
+                    Weight::Normal => fontconfig::Weight::Regular,
+                });
+            },
+        }
+
+        pattern.font_match().filename().ok_or_else(|| Error::FontNotFound(desc.to_owned()))
+    }
+
+    /// Build the fallback cascade list for a font, used when a glyph is missing from the primary
+    /// face. Mirrors `darwin::cascade_list_for_languages`, but fontconfig already bakes language
+    /// coverage into its match ordering so we just ask for the default `sans-serif` alias chain.
+    fn cascade(&self, languages: &[String]) -> Vec<String> {
+        let mut pattern = Pattern::new(&self.fontconfig);
+        for language in languages {
+            pattern.add_lang(language);
+        }
+        pattern.add_family("sans-serif");
+        pattern.font_sort().into_iter().filter_map(|font| font.filename()).collect()
+    }
+}
+
+/// FreeType's BGRA color-glyph buffer, premultiplied-alpha, reordered to RGBA.
+fn bgra_to_rgba(buffer: &[u8]) -> Vec<u8> {
+    let mut rgba = Vec::with_capacity(buffer.len());
+    for chunk in buffer.chunks_exact(4) {
+        let (b, g, r, a) = (chunk[0], chunk[1], chunk[2], chunk[3]);
+        rgba.push(r);
+        rgba.push(g);
+        rgba.push(b);
+        rgba.push(a);
+    }
+    rgba
+}
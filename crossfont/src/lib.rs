@@ -1,155 +1,65 @@
-use std::collections::HashMap;
-use std::iter;
 use std::ops::{Add, Mul};
-use std::path::PathBuf;
-use std::ptr;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
-use core_foundation::array::{CFArray, CFIndex};
-use core_foundation::base::{CFType, ItemRef, TCFType};
-use core_foundation::number::{CFNumber, CFNumberRef};
-use core_foundation::string::CFString;
-use core_graphics::base::kCGImageAlphaPremultipliedFirst;
-use core_graphics::color_space::CGColorSpace;
-use core_graphics::context::CGContext;
-use core_graphics::font::CGGlyph;
-use core_graphics::geometry::{CGPoint, CGRect, CGSize};
-use core_text::font::{
-    cascade_list_for_languages as ct_cascade_list_for_languages, new_from_descriptor,
-    new_from_name, CTFont,
-};
-use core_text::font_collection::create_for_family;
-use core_text::font_descriptor::{
-    self, kCTFontColorGlyphsTrait, kCTFontDefaultOrientation, kCTFontEnabledAttribute,
-    CTFontDescriptor, SymbolicTraitAccessors,
-};
-
-use log::{trace, warn};
-
 /// According to the documentation, the index of 0 must be a missing glyph character:
 /// https://developer.apple.com/fonts/TrueType-Reference-Manual/RM07/appendixB.html
-const MISSING_GLYPH_INDEX: u32 = 0;
-
-pub mod darwin;
-use darwin::kCGBitmapByteOrder32Host;
+///
+/// This convention (glyph index 0 is `.notdef`) isn't CoreText-specific -- FreeType's
+/// `FT_Get_Char_Index` and DirectWrite's `GetGlyphIndices` both use it too -- so every backend
+/// shares the same sentinel.
+pub(crate) const MISSING_GLYPH_INDEX: u32 = 0;
+
+#[cfg(target_os = "macos")]
+mod darwin;
+#[cfg(target_os = "macos")]
+pub use darwin::Rasterizer;
+
+#[cfg(not(any(target_os = "macos", windows)))]
+mod ft;
+#[cfg(not(any(target_os = "macos", windows)))]
+pub use ft::Rasterizer;
+
+#[cfg(windows)]
+mod directwrite;
+#[cfg(windows)]
+pub use directwrite::Rasterizer;
 
-pub struct Rasterizer {
-    fonts: HashMap<FontKey, Font>,
-    keys: HashMap<(FontDesc, Size), FontKey>,
-    device_pixel_ratio: f32,
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FontDesc {
+    pub(crate) name: String,
+    pub(crate) style: Style,
+    pub(crate) variations: Vec<FontVariation>,
 }
 
-impl Rasterize for Rasterizer {
-    fn new(device_pixel_ratio: f32) -> Rasterizer {
-        Rasterizer { fonts: HashMap::new(), keys: HashMap::new(), device_pixel_ratio }
-    }
-
-    /// Get metrics for font specified by FontKey.
-    fn metrics(&self, key: FontKey, _size: Size) -> Metrics {
-        let font = self.fonts.get(&key).ok_or(Error::UnknownFontKey).unwrap();
-        font.metrics()
-    }
-
-    fn load_font(&mut self, desc: &FontDesc, size: Size) -> Result<FontKey, Error> {
-        let scaled_size = Size::new(size.as_f32_pts() * self.device_pixel_ratio);
-        self.keys.get(&(desc.to_owned(), scaled_size)).map(|k| Ok(*k)).unwrap_or_else(|| {
-            let font = self.get_font(desc, size)?;
-            let key = FontKey::next();
-
-            self.fonts.insert(key, font);
-            self.keys.insert((desc.clone(), scaled_size), key);
-
-            Ok(key)
-        })
-    }
-
-    /// Get rasterized glyph for given glyph key.
-    fn get_glyph(&mut self, glyph: GlyphKey) -> Result<RasterizedGlyph, Error> {
-        // Get loaded font.
-        let font = self.fonts.get(&glyph.font_key).ok_or(Error::UnknownFontKey)?;
-
-        // Find a font where the given character is present.
-        let (font, glyph_index) = iter::once(font)
-            .chain(font.fallbacks.iter())
-            .find_map(|font| match font.glyph_index(glyph.character) {
-                MISSING_GLYPH_INDEX => None,
-                glyph_index => Some((font, glyph_index)),
-            })
-            .unwrap_or((font, MISSING_GLYPH_INDEX));
-
-        let glyph = font.get_glyph(glyph.character, glyph_index);
-
-        if glyph_index == MISSING_GLYPH_INDEX {
-            Err(Error::MissingGlyph(glyph))
-        } else {
-            Ok(glyph)
-        }
-    }
-
-    fn update_dpr(&mut self, device_pixel_ratio: f32) {
-        self.device_pixel_ratio = device_pixel_ratio;
-    }
+/// An OpenType font-variation axis setting, imported from WebRender's `FontVariation` concept.
+///
+/// E.g. `FontVariation { tag: *b"wght", value: 350.0 }` requests a weight of 350 on a variable
+/// font, rather than only the discrete [`Weight::Bold`]/[`Weight::Normal`] faces [`Style`]
+/// supports.
+#[derive(Debug, Clone, Copy)]
+pub struct FontVariation {
+    pub tag: [u8; 4],
+    pub value: f32,
 }
 
-impl Rasterizer {
-    fn get_specific_face(
-        &mut self,
-        desc: &FontDesc,
-        style: &str,
-        size: Size,
-    ) -> Result<Font, Error> {
-        let descriptors = descriptors_for_family(&desc.name[..]);
-        for descriptor in descriptors {
-            if descriptor.style_name == style {
-                // Found the font we want.
-                let scaled_size = f64::from(size.as_f32_pts()) * f64::from(self.device_pixel_ratio);
-                let font = descriptor.to_font(scaled_size, true);
-                return Ok(font);
-            }
-        }
-
-        Err(Error::FontNotFound(desc.to_owned()))
+// `f32` isn't `Eq`/`Hash`; `FontDesc` (which embeds a `Vec<FontVariation>`) is used as a
+// `HashMap` key in `Rasterizer::keys`; compare/hash the bit pattern instead so distinct
+// instances of one variable face are cached separately.
+impl PartialEq for FontVariation {
+    fn eq(&self, other: &Self) -> bool {
+        self.tag == other.tag && self.value.to_bits() == other.value.to_bits()
     }
+}
 
-    fn get_matching_face(
-        &mut self,
-        desc: &FontDesc,
-        slant: Slant,
-        weight: Weight,
-        size: Size,
-    ) -> Result<Font, Error> {
-        let bold = weight == Weight::Bold;
-        let italic = slant != Slant::Normal;
-        let scaled_size = f64::from(size.as_f32_pts()) * f64::from(self.device_pixel_ratio);
-
-        let descriptors = descriptors_for_family(&desc.name[..]);
-        for descriptor in descriptors {
-            let font = descriptor.to_font(scaled_size, true);
-            if font.is_bold() == bold && font.is_italic() == italic {
-                // Found the font we want.
-                return Ok(font);
-            }
-        }
-
-        Err(Error::FontNotFound(desc.to_owned()))
-    }
+impl Eq for FontVariation {}
 
-    fn get_font(&mut self, desc: &FontDesc, size: Size) -> Result<Font, Error> {
-        match desc.style {
-            Style::Specific(ref style) => self.get_specific_face(desc, style, size),
-            Style::Description { slant, weight } => {
-                self.get_matching_face(desc, slant, weight, size)
-            },
-        }
+impl std::hash::Hash for FontVariation {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.tag.hash(state);
+        self.value.to_bits().hash(state);
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct FontDesc {
-    name: String,
-    style: Style,
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Slant {
     Normal,
@@ -175,7 +85,14 @@ impl FontDesc {
     where
         S: Into<String>,
     {
-        FontDesc { name: name.into(), style }
+        FontDesc { name: name.into(), style, variations: Vec::new() }
+    }
+
+    /// Request the given OpenType variation-axis settings on this face, e.g. `wght=350` or
+    /// `opsz=12` on a variable font.
+    pub fn with_variations(mut self, variations: Vec<FontVariation>) -> FontDesc {
+        self.variations = variations;
+        self
     }
 }
 
@@ -247,6 +164,19 @@ impl From<f32> for Size {
     }
 }
 
+/// Width/height/top/left/advance for a glyph, without the rasterized bitmap -- the same layout
+/// metrics [`RasterizedGlyph`] carries, for callers (layout, atlas reservation) that need to
+/// measure a glyph before, or instead of, rasterizing it. Mirrors WebRender's
+/// `get_glyph_dimensions` entry point.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct GlyphDimensions {
+    pub width: i32,
+    pub height: i32,
+    pub top: i32,
+    pub left: i32,
+    pub advance: (i32, i32),
+}
+
 #[derive(Debug, Clone)]
 pub struct RasterizedGlyph {
     pub character: char,
@@ -260,7 +190,9 @@ pub struct RasterizedGlyph {
 
 #[derive(Clone, Debug)]
 pub enum BitmapBuffer {
-    /// RGB alphamask.
+    /// RGB alphamask. Under [`FontRenderMode::Mono`]/[`FontRenderMode::GrayscaleAa`] the three
+    /// bytes are a single coverage value replicated across all channels; under
+    /// [`FontRenderMode::SubpixelAa`] they are independent per-channel subpixel coverage instead.
     Rgb(Vec<u8>),
 
     /// RGBA pixels with premultiplied alpha.
@@ -311,6 +243,19 @@ pub enum Error {
     PlatformError(String),
 }
 
+/// How a rasterizer should antialias the glyphs it produces.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FontRenderMode {
+    /// No antialiasing; coverage is thresholded to fully on/off.
+    Mono,
+    /// A single antialiased coverage value per pixel, replicated across all channels.
+    GrayscaleAa,
+    /// Independent coverage per subpixel channel, for sharper text on LCD panels. Still carried
+    /// in [`BitmapBuffer::Rgb`], but the three bytes are now per-channel coverage rather than a
+    /// replicated luminance value.
+    SubpixelAa,
+}
+
 pub trait Rasterize {
     /// Create a new Rasterizer.
     fn new(device_pixel_ratio: f32) -> Self
@@ -318,7 +263,7 @@ pub trait Rasterize {
         Self: Sized;
 
     /// Get `Metrics` for the given `FontKey`.
-    fn metrics(&self, _: FontKey, _: Size) -> Metrics;
+    fn metrics(&self, _: FontKey, _: Size) -> Result<Metrics, Error>;
 
     /// Load the font described by `FontDesc` and `Size`.
     fn load_font(&mut self, _: &FontDesc, _: Size) -> Result<FontKey, Error>;
@@ -326,291 +271,13 @@ pub trait Rasterize {
     /// Rasterize the glyph described by `GlyphKey`..
     fn get_glyph(&mut self, _: GlyphKey) -> Result<RasterizedGlyph, Error>;
 
+    /// Measure the glyph described by `GlyphKey` without rasterizing a bitmap for it.
+    fn glyph_dimensions(&mut self, _: GlyphKey) -> Result<GlyphDimensions, Error>;
+
     /// Update the Rasterizer's DPI factor.
     fn update_dpr(&mut self, device_pixel_ratio: f32);
-}
-
-/// A font.
-#[derive(Clone)]
-struct Font {
-    ct_font: CTFont,
-    fallbacks: Vec<Font>,
-}
-
-unsafe impl Send for Font {}
-
-impl Font {
-    fn metrics(&self) -> Metrics {
-        let average_advance = self.glyph_advance('0');
-
-        let ascent = self.ct_font.ascent().round() as f64;
-        let descent = self.ct_font.descent().round() as f64;
-        let leading = self.ct_font.leading().round() as f64;
-        let line_height = ascent + descent + leading;
-
-        // Strikeout and underline metrics.
-        // CoreText doesn't provide strikeout so we provide our own.
-        let underline_position = self.ct_font.underline_position() as f32;
-        let underline_thickness = self.ct_font.underline_thickness() as f32;
-        let strikeout_position = (line_height / 2. - descent) as f32;
-        let strikeout_thickness = underline_thickness;
-
-        Metrics {
-            average_advance,
-            line_height,
-            descent: -(descent as f32),
-            underline_position,
-            underline_thickness,
-            strikeout_position,
-            strikeout_thickness,
-        }
-    }
-
-    fn is_bold(&self) -> bool {
-        self.ct_font.symbolic_traits().is_bold()
-    }
-
-    fn is_italic(&self) -> bool {
-        self.ct_font.symbolic_traits().is_italic()
-    }
-
-    fn is_colored(&self) -> bool {
-        (self.ct_font.symbolic_traits() & kCTFontColorGlyphsTrait) != 0
-    }
-
-    fn glyph_advance(&self, character: char) -> f64 {
-        let index = self.glyph_index(character);
-
-        let indices = [index as CGGlyph];
-
-        unsafe {
-            self.ct_font.get_advances_for_glyphs(
-                kCTFontDefaultOrientation,
-                &indices[0],
-                ptr::null_mut(),
-                1,
-            )
-        }
-    }
-
-    fn get_glyph(&self, character: char, glyph_index: u32) -> RasterizedGlyph {
-        let bounds = self
-            .ct_font
-            .get_bounding_rects_for_glyphs(kCTFontDefaultOrientation, &[glyph_index as CGGlyph]);
-
-        let rasterized_left = bounds.origin.x.floor() as i32;
-        let rasterized_width =
-            (bounds.origin.x - f64::from(rasterized_left) + bounds.size.width).ceil() as u32;
-        let rasterized_descent = (-bounds.origin.y).ceil() as i32;
-        let rasterized_ascent = (bounds.size.height + bounds.origin.y).ceil() as i32;
-        let rasterized_height = (rasterized_descent + rasterized_ascent) as u32;
-
-        if rasterized_width == 0 || rasterized_height == 0 {
-            return RasterizedGlyph {
-                character: ' ',
-                width: 0,
-                height: 0,
-                top: 0,
-                left: 0,
-                advance: (0, 0),
-                buffer: BitmapBuffer::Rgb(Vec::new()),
-            };
-        }
-
-        let mut cg_context = CGContext::create_bitmap_context(
-            None,
-            rasterized_width as usize,
-            rasterized_height as usize,
-            8, // bits per component
-            rasterized_width as usize * 4,
-            &CGColorSpace::create_device_rgb(),
-            kCGImageAlphaPremultipliedFirst | kCGBitmapByteOrder32Host,
-        );
-
-        let is_colored = self.is_colored();
-
-        // Set background color for graphics context.
-        let bg_a = if is_colored { 0.0 } else { 1.0 };
-        cg_context.set_rgb_fill_color(0.0, 0.0, 0.0, bg_a);
-
-        let context_rect = CGRect::new(
-            &CGPoint::new(0.0, 0.0),
-            &CGSize::new(f64::from(rasterized_width), f64::from(rasterized_height)),
-        );
-
-        cg_context.fill_rect(context_rect);
-
-        cg_context.set_allows_font_smoothing(true);
-        cg_context.set_should_smooth_fonts(false);
-        cg_context.set_allows_font_subpixel_quantization(true);
-        cg_context.set_should_subpixel_quantize_fonts(true);
-        cg_context.set_allows_font_subpixel_positioning(true);
-        cg_context.set_should_subpixel_position_fonts(true);
-        cg_context.set_allows_antialiasing(true);
-        cg_context.set_should_antialias(true);
-
-        // Set fill color to white for drawing the glyph.
-        cg_context.set_rgb_fill_color(1.0, 1.0, 1.0, 1.0);
-        let rasterization_origin =
-            CGPoint { x: f64::from(-rasterized_left), y: f64::from(rasterized_descent) };
-
-        self.ct_font.draw_glyphs(
-            &[glyph_index as CGGlyph],
-            &[rasterization_origin],
-            cg_context.clone(),
-        );
-
-        let rasterized_pixels = cg_context.data().to_vec();
-
-        let buffer = if is_colored {
-            BitmapBuffer::Rgba(darwin::extract_rgba(&rasterized_pixels))
-        } else {
-            BitmapBuffer::Rgb(darwin::extract_rgb(&rasterized_pixels))
-        };
-
-        RasterizedGlyph {
-            character,
-            left: rasterized_left,
-            top: (bounds.size.height + bounds.origin.y).ceil() as i32,
-            width: rasterized_width as i32,
-            height: rasterized_height as i32,
-            advance: (0, 0),
-            buffer,
-        }
-    }
-
-    fn glyph_index(&self, character: char) -> u32 {
-        // Encode this char as utf-16.
-        let mut buffer = [0; 2];
-        let encoded: &[u16] = character.encode_utf16(&mut buffer);
-        // And use the utf-16 buffer to get the index.
-        self.glyph_index_utf16(encoded)
-    }
-
-    fn glyph_index_utf16(&self, encoded: &[u16]) -> u32 {
-        // Output buffer for the glyph. for non-BMP glyphs, like
-        // emojis, this will be filled with two chars the second
-        // always being a 0.
-        let mut glyphs: [CGGlyph; 2] = [0; 2];
-
-        let res = unsafe {
-            self.ct_font.get_glyphs_for_characters(
-                encoded.as_ptr(),
-                glyphs.as_mut_ptr(),
-                encoded.len() as CFIndex,
-            )
-        };
-
-        if res {
-            u32::from(glyphs[0])
-        } else {
-            MISSING_GLYPH_INDEX
-        }
-    }
-}
-
-/// Font descriptor.
-///
-/// The descriptor provides data about a font and supports creating a font.
-#[derive(Debug)]
-struct Descriptor {
-    style_name: String,
-    font_path: PathBuf,
-
-    ct_descriptor: CTFontDescriptor,
-}
-
-impl Descriptor {
-    fn new(desc: CTFontDescriptor) -> Descriptor {
-        Descriptor {
-            style_name: desc.style_name(),
-            font_path: desc.font_path().unwrap_or_else(PathBuf::new),
-            ct_descriptor: desc,
-        }
-    }
-
-    /// Create a Font from this descriptor.
-    fn to_font(&self, size: f64, load_fallbacks: bool) -> Font {
-        let ct_font = new_from_descriptor(&self.ct_descriptor, size);
-
-        let fallbacks = if load_fallbacks {
-            // TODO fixme, hardcoded en for english.
-            let mut fallbacks = cascade_list_for_languages(&ct_font, &["en".to_owned()])
-                .into_iter()
-                .filter(|desc| !desc.font_path.as_os_str().is_empty())
-                .map(|desc| desc.to_font(size, false))
-                .collect::<Vec<_>>();
-
-            // TODO, we can't use apple's proposed
-            // .Apple Symbol Fallback (filtered out below),
-            // but not having these makes us not able to render
-            // many chars. We add the symbols back in.
-            // Investigate if we can actually use the .-prefixed
-            // fallbacks somehow.
-            if let Ok(apple_symbols) = new_from_name("Apple Symbols", size) {
-                fallbacks.push(Font { ct_font: apple_symbols, fallbacks: Vec::new() })
-            };
-
-            fallbacks
-        } else {
-            Vec::new()
-        };
-
-        Font { ct_font, fallbacks }
-    }
-}
-
-/// Return fallback descriptors for font/language list.
-fn cascade_list_for_languages(ct_font: &CTFont, languages: &[String]) -> Vec<Descriptor> {
-    // Convert language type &Vec<String> -> CFArray.
-    let langarr: CFArray<CFString> = {
-        let tmp: Vec<CFString> = languages.iter().map(|language| CFString::new(language)).collect();
-        CFArray::from_CFTypes(&tmp)
-    };
-
-    // CFArray of CTFontDescriptorRef (again).
-    let list = ct_cascade_list_for_languages(ct_font, &langarr);
-
-    // Convert CFArray to Vec<Descriptor>.
-    list.into_iter().filter(is_enabled).map(|fontdesc| Descriptor::new(fontdesc.clone())).collect()
-}
-
-/// Check if a font is enabled.
-fn is_enabled(fontdesc: &ItemRef<'_, CTFontDescriptor>) -> bool {
-    unsafe {
-        let descriptor = fontdesc.as_concrete_TypeRef();
-        let attr_val =
-            font_descriptor::CTFontDescriptorCopyAttribute(descriptor, kCTFontEnabledAttribute);
-
-        if attr_val.is_null() {
-            return false;
-        }
-
-        let attr_val = CFType::wrap_under_create_rule(attr_val);
-        let attr_val = CFNumber::wrap_under_get_rule(attr_val.as_CFTypeRef() as CFNumberRef);
-
-        attr_val.to_i32().unwrap_or(0) != 0
-    }
-}
-
-/// Get descriptors for family name.
-fn descriptors_for_family(family: &str) -> Vec<Descriptor> {
-    let mut out = Vec::new();
-
-    trace!("Family: {}", family);
-    let ct_collection = create_for_family(family).unwrap_or_else(|| {
-        // Fallback to Menlo if we can't find the config specified font family.
-        warn!("Unable to load specified font {}, falling back to Menlo", &family);
-        create_for_family("Menlo").expect("Menlo exists")
-    });
-
-    // CFArray of CTFontDescriptorRef (i think).
-    let descriptors = ct_collection.get_descriptors();
-    if let Some(descriptors) = descriptors {
-        for descriptor in descriptors.iter() {
-            out.push(Descriptor::new(descriptor.clone()));
-        }
-    }
 
-    out
+    /// Select how subsequent calls to [`Self::get_glyph`] antialias their output. Backends that
+    /// don't support a mode (e.g. no dual-background subpixel trick) may ignore this.
+    fn set_render_mode(&mut self, _render_mode: FontRenderMode) {}
 }
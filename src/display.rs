@@ -1,17 +1,31 @@
-use std::mem::ManuallyDrop;
-use std::ops::Deref;
+use winit::dpi::PhysicalSize;
 
-use glutin::context::{NotCurrentContext, PossiblyCurrentContext};
-use glutin::prelude::*;
-use glutin::surface::{Surface, WindowSurface};
+use glutin::config::Config as GlConfig;
+use glutin::context::NotCurrentContext;
+use glutin::display::Display as GlDisplay;
 
-use crossfont::{FontDesc, FontKey, Rasterizer, Size};
+use crate::display::window::Window;
+use crate::renderer::render_thread::RenderThread;
 
-use raw_window_handle::HasRawWindowHandle;
+pub mod window;
 
-use winit::window::Window;
+/// A 24-bit RGB color.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
 
-use crate::renderer::{self, Glsl3Renderer};
+    pub fn as_tuple(self) -> (u8, u8, u8) {
+        (self.r, self.g, self.b)
+    }
+}
 
 /// Terminal size info.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -42,97 +56,87 @@ impl SizeInfo<f32> {
             padding_y: padding_y.floor(),
         }
     }
-}
 
-/// The display wraps a window, font rasterizer, and GPU renderer.
-pub struct Display {
-    pub window: Window,
+    pub fn width(&self) -> f32 {
+        self.width
+    }
 
-    pub size_info: SizeInfo,
+    pub fn height(&self) -> f32 {
+        self.height
+    }
 
-    renderer: ManuallyDrop<Glsl3Renderer>,
+    pub fn cell_width(&self) -> f32 {
+        self.cell_width
+    }
 
-    surface: ManuallyDrop<Surface<WindowSurface>>,
+    pub fn cell_height(&self) -> f32 {
+        self.cell_height
+    }
 
-    context: PossiblyCurrentContext,
+    pub fn padding_x(&self) -> f32 {
+        self.padding_x
+    }
 
-    rasterizer: Rasterizer,
+    pub fn padding_y(&self) -> f32 {
+        self.padding_y
+    }
+}
 
-    font_key: FontKey,
+/// The display wraps a window and hands off the font rasterizer, GPU renderer, and GL
+/// context/surface to a dedicated [`RenderThread`], so rasterizing glyphs and presenting frames
+/// never blocks this (the winit event loop's) thread.
+pub struct Display {
+    pub window: Window,
 
-    font_size: Size,
+    render_thread: RenderThread,
 }
 
 impl Display {
-    pub fn new(window: Window, gl_context: NotCurrentContext) -> Display {
-        let mut rasterizer = Rasterizer::new(window.scale_factor() as f32);
-
-        let font_name = String::from("Source Code Pro");
-        let font_size = Size::new(16.);
-        let regular_desc = FontDesc::new(&font_name, &String::from("Regular"));
-        let font_key = rasterizer.load_font(&regular_desc, font_size).unwrap();
-
-        let offset_x = 1 as f64;
-        let offset_y = 2 as f64;
-        let metrics = rasterizer.metrics(font_key, font_size);
-        println!(
-            "average_advance = {}, line_height = {}",
-            metrics.average_advance, metrics.line_height
-        );
-        let cell_width = (metrics.average_advance + offset_x).floor().max(1.) as f32;
-        let cell_height = (metrics.line_height + offset_y).floor().max(1.) as f32;
-        println!("cell_width = {}, cell_height = {}", cell_width, cell_height);
-
-        // Create the GL surface to draw into.
-        let viewport_size = window.inner_size();
-        let surface = renderer::platform::create_gl_surface(
-            &gl_context,
-            viewport_size,
-            window.raw_window_handle(),
-        );
-
-        let context = gl_context.make_current(&surface).unwrap();
-
-        let renderer = Glsl3Renderer::new(&context);
-
-        // Create new size with at least one column and row.
-        let size_info = SizeInfo::new(
-            viewport_size.width as f32,
-            viewport_size.height as f32,
-            cell_width,
-            cell_height,
-            5. * (window.scale_factor() as f32),
-            5. * (window.scale_factor() as f32),
-        );
+    /// `gl_display` and `gl_config` are kept by the render thread (not just `gl_context`) so it
+    /// can recreate a lost GL context on its own, without a round trip through the event loop.
+    pub fn new(
+        window: Window,
+        gl_display: GlDisplay,
+        gl_config: GlConfig,
+        gl_context: NotCurrentContext,
+    ) -> Display {
+        let render_thread = RenderThread::spawn(&window, gl_display, gl_config, gl_context);
 
         window.set_visible(true);
 
-        Self {
-            window,
-            context,
-            surface: ManuallyDrop::new(surface),
-            renderer: ManuallyDrop::new(renderer),
-            size_info,
-            rasterizer,
-            font_key,
-            font_size,
-        }
+        Self { window, render_thread }
     }
 
-    pub fn make_current(&self) {
-        if !self.context.is_current() {
-            self.context.make_current(&self.surface).expect("failed to make context current")
-        }
+    /// The most recently computed cell/window geometry, reported back by the render thread.
+    pub fn size_info(&self) -> SizeInfo {
+        self.render_thread.size_info()
     }
 
-    pub fn draw(&mut self) {
-        self.make_current();
+    /// Whether the render thread has presented at least one frame yet.
+    pub fn has_frame(&self) -> bool {
+        self.render_thread.has_frame()
+    }
+
+    /// Flip the built-in debug/performance overlay on or off.
+    pub fn toggle_debug_overlay(&mut self) {
+        self.render_thread.toggle_debug_overlay();
+    }
 
-        self.renderer.draw_cells(&mut self.rasterizer, self.font_key, self.font_size);
+    /// React to a winit `Resized` event by forwarding it to the render thread, which resizes the
+    /// GL surface and recomputes `SizeInfo` for the new framebuffer dimensions.
+    pub fn handle_resize(&mut self, new_size: PhysicalSize<u32>) {
+        self.render_thread.resize(new_size);
+    }
 
-        // Clearing debug highlights from the previous frame requires full redraw.
-        let _ = match (self.surface.deref(), &self.context) {
-            (surface, context) => surface.swap_buffers(context),
-        };
+    /// React to a winit `ScaleFactorChanged` event by forwarding it to the render thread, which
+    /// re-requests glyph metrics at the new device pixel ratio and clears the glyph atlas so
+    /// glyphs rasterized at the old scale aren't reused and rendered blurry.
+    pub fn handle_scale_factor_change(&mut self, scale_factor: f64) {
+        self.render_thread.set_scale_factor(scale_factor);
+    }
+
+    /// Ask the render thread to draw and present a frame. Returns immediately.
+    pub fn draw(&mut self) {
+        self.render_thread.request_redraw();
     }
 }
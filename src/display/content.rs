@@ -1,5 +1,5 @@
 use crate::display::{Rgb, SizeInfo};
-use crate::renderer::rects::RenderRect;
+use crate::renderer::rects::{RectKind, RenderRect};
 
 /// Cell ready for rendering.
 #[derive(Clone, Debug)]
@@ -11,6 +11,69 @@ pub struct RenderableCell {
     pub bg: Rgb,
     pub bg_alpha: f32,
     pub underline: Rgb,
+    pub underline_style: UnderlineStyle,
+}
+
+impl RenderableCell {
+    /// This cell's underline quad for [`crate::renderer::rects::RectRenderer`], or `None` when
+    /// `underline_style` is [`UnderlineStyle::None`]. Sits along the cell's bottom edge, the same
+    /// position [`RenderableCursor::rects`]'s `Underline` style draws its bar.
+    pub fn underline_rect(&self, size_info: &SizeInfo) -> Option<RenderRect> {
+        let kind = match self.underline_style {
+            UnderlineStyle::None => return None,
+            UnderlineStyle::Line => RectKind::Normal,
+            UnderlineStyle::Undercurl => RectKind::Undercurl,
+            UnderlineStyle::Dotted => RectKind::DottedUnderline,
+            UnderlineStyle::Dashed => RectKind::DashedUnderline,
+        };
+
+        let x = self.column as f32 * size_info.cell_width + size_info.padding_x;
+        let y = self.line as f32 * size_info.cell_height + size_info.padding_y;
+        let width = size_info.cell_width;
+        let height = (0.15 * size_info.cell_width).round().max(1.);
+
+        Some(RenderRect {
+            kind,
+            x,
+            y: y + size_info.cell_height - height,
+            width,
+            height,
+            color: self.underline,
+            alpha: 1.,
+        })
+    }
+}
+
+/// Style of underline a cell is drawn with, mapped to a [`RectKind`] by
+/// [`RenderableCell::underline_rect`].
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
+pub enum UnderlineStyle {
+    /// No underline.
+    #[default]
+    None,
+    /// A solid horizontal bar.
+    Line,
+    /// A wavy line, typically used for spelling/syntax errors.
+    Undercurl,
+    /// A dotted horizontal bar.
+    Dotted,
+    /// A dashed horizontal bar.
+    Dashed,
+}
+
+/// The cursor's caret shape.
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
+pub enum CursorStyle {
+    /// A solid block covering the whole cell; the glyph underneath must be drawn in a
+    /// contrasting color to stay legible.
+    #[default]
+    Block,
+    /// A thin vertical bar at the cell's left edge.
+    Beam,
+    /// A thin horizontal bar at the cell's bottom edge.
+    Underline,
+    /// `Block`'s outline instead of a solid fill.
+    HollowBlock,
 }
 
 /// Cursor storing all information relevant for rendering.
@@ -18,19 +81,34 @@ pub struct RenderableCell {
 pub struct RenderableCursor {
     pub point: Point,
     pub color: Rgb,
+    pub style: CursorStyle,
 }
 
 impl RenderableCursor {
-    pub fn rects(self, size_info: &SizeInfo, thickness: f32) -> RenderRect {
+    /// Quad(s) making up this cursor's outline for [`crate::renderer::rects::RectRenderer`],
+    /// shaped by `self.style`: a solid cell for `Block`, a thin bar on the left/bottom edge for
+    /// `Beam`/`Underline`, and four thin bars tracing the cell's border for `HollowBlock`.
+    pub fn rects(self, size_info: &SizeInfo, thickness: f32) -> Vec<RenderRect> {
         let x = self.point.column as f32 * size_info.cell_width + size_info.padding_x;
         let y = self.point.line as f32 * size_info.cell_height + size_info.padding_y;
 
         let width = size_info.cell_width;
         let height = size_info.cell_height;
+        let bar = (thickness * width).round().max(1.);
 
-        let thickness = (thickness * width).round().max(1.);
+        let rect = |x, y, width, height| RenderRect::new(x, y, width, height, self.color, 1.);
 
-        RenderRect::new(x, y, thickness, height, self.color, 1.).into()
+        match self.style {
+            CursorStyle::Block => vec![rect(x, y, width, height)],
+            CursorStyle::Beam => vec![rect(x, y, bar, height)],
+            CursorStyle::Underline => vec![rect(x, y + height - bar, width, bar)],
+            CursorStyle::HollowBlock => vec![
+                rect(x, y, width, bar),               // Top edge.
+                rect(x, y + height - bar, width, bar), // Bottom edge.
+                rect(x, y, bar, height),               // Left edge.
+                rect(x + width - bar, y, bar, height), // Right edge.
+            ],
+        }
     }
 }
 
@@ -45,3 +123,77 @@ impl Point {
         Point { line, column }
     }
 }
+
+/// A pixel-space rectangle marking part of the surface that changed since the last frame, ready
+/// to hand to glutin's `swap_buffers_with_damage` once converted to its own `Rect` type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DamageRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl DamageRect {
+    /// The pixel-space rectangle a single cell at `(line, column)` occupies.
+    pub fn for_cell(line: usize, column: usize, size_info: &SizeInfo) -> Self {
+        let x = (column as f32 * size_info.cell_width + size_info.padding_x) as i32;
+        let y = (line as f32 * size_info.cell_height + size_info.padding_y) as i32;
+
+        let width = size_info.cell_width.ceil() as i32;
+        let height = size_info.cell_height.ceil() as i32;
+
+        Self { x, y, width, height }
+    }
+
+    /// A single rectangle covering the entire surface, used when there's no previous frame to
+    /// diff against (or a resize/scale-factor change moved every pixel).
+    pub fn full(size_info: &SizeInfo) -> Self {
+        let width = size_info.width.ceil() as i32;
+        let height = size_info.height.ceil() as i32;
+
+        Self { x: 0, y: 0, width, height }
+    }
+
+    /// Whether `self` and `other` touch or overlap, so they can be coalesced into one rectangle
+    /// without covering much extra, undamaged surface.
+    fn touches(&self, other: &Self) -> bool {
+        let self_right = self.x + self.width;
+        let other_right = other.x + other.width;
+        let self_bottom = self.y + self.height;
+        let other_bottom = other.y + other.height;
+
+        self.x <= other_right
+            && other.x <= self_right
+            && self.y <= other_bottom
+            && other.y <= self_bottom
+    }
+
+    /// The smallest rectangle covering both `self` and `other`.
+    fn union(&self, other: &Self) -> Self {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+
+        Self { x, y, width: right - x, height: bottom - y }
+    }
+}
+
+/// Coalesce a set of per-cell damage rectangles into a smaller set of bounding rectangles, so a
+/// large contiguous edit doesn't turn into hundreds of one-cell damage regions.
+pub fn coalesce_rects(rects: Vec<DamageRect>) -> Vec<DamageRect> {
+    let mut merged: Vec<DamageRect> = Vec::new();
+
+    'rects: for rect in rects {
+        for existing in merged.iter_mut() {
+            if existing.touches(&rect) {
+                *existing = existing.union(&rect);
+                continue 'rects;
+            }
+        }
+        merged.push(rect);
+    }
+
+    merged
+}
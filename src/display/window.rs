@@ -70,21 +70,28 @@ pub struct Window {
 impl Window {
     /// Create a new window.
     ///
-    /// This creates a window and fully initializes a window.
-    pub fn new<E>(event_loop: &EventLoopWindowTarget<E>) -> Result<Window> {
+    /// This creates a window and fully initializes a window. `background_opacity` below `1.0`
+    /// makes the window transparent, so renderer-drawn pixels with less-than-opaque alpha let the
+    /// desktop show through. The alpha-backed framebuffer config is picked separately, and
+    /// always, by `renderer::platform::pick_gl_config`.
+    pub fn new<E>(
+        event_loop: &EventLoopWindowTarget<E>,
+        background_opacity: f32,
+    ) -> Result<Window> {
         let window_builder = Window::get_platform_window();
+        let transparent = background_opacity < 1.0;
 
         let window = window_builder
             .with_title("GlyphAtlas")
             .with_theme(None)
             .with_visible(false)
-            .with_transparent(false)
+            .with_transparent(transparent)
             .with_maximized(true)
             .with_fullscreen(None)
             .build(event_loop)?;
 
         // Set initial transparency hint.
-        window.set_transparent(false);
+        window.set_transparent(transparent);
 
         let scale_factor = window.scale_factor();
 
@@ -115,4 +122,24 @@ impl Window {
     pub fn id(&self) -> WindowId {
         self.window.id()
     }
+
+    /// Minimize or restore the window.
+    pub fn set_minimized(&self, minimized: bool) {
+        self.window.set_minimized(minimized);
+    }
+
+    /// Switch to the tab before this window's in the owning tab group.
+    ///
+    /// This editor doesn't group windows into tabs yet -- each window opened with
+    /// [`Action::CreateWindow`](crate::input::Action::CreateWindow) is its own top-level window --
+    /// so this is a no-op until that lands.
+    pub fn select_previous_tab(&mut self) {}
+
+    /// Switch to the tab after this window's in the owning tab group. See
+    /// [`Window::select_previous_tab`].
+    pub fn select_next_tab(&mut self) {}
+
+    /// Switch to the `index`'th tab (1-based) in the owning tab group. See
+    /// [`Window::select_previous_tab`].
+    pub fn select_tab(&mut self, _index: u8) {}
 }
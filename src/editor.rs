@@ -1,3 +1,4 @@
+use crate::display::content::CursorStyle;
 use crate::editor::buffer::Buffer;
 
 pub mod buffer;
@@ -15,4 +16,14 @@ impl Editor {
     pub fn buffer_mut(&mut self) -> &mut Buffer {
         &mut self.buffer
     }
+
+    /// Change the active buffer's cursor caret shape.
+    pub fn set_cursor_style(&mut self, style: CursorStyle) {
+        self.buffer.set_cursor_style(style);
+    }
+
+    /// Flip the active buffer's cursor visibility, for callers driving a blink timer.
+    pub fn toggle_cursor_visible(&mut self) {
+        self.buffer.toggle_cursor_visible();
+    }
 }
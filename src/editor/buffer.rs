@@ -1,16 +1,48 @@
 use std::cmp;
+use std::collections::HashMap;
 
 use crop::Rope;
 use unicode_segmentation::GraphemeCursor;
 
-use crate::display::content::{RenderableCell, RenderableCursor};
-use crate::display::Rgb;
+use crate::display::content::{
+    coalesce_rects, CursorStyle, DamageRect, RenderableCell, RenderableCursor, UnderlineStyle,
+};
+use crate::display::{Rgb, SizeInfo};
 
-#[derive(Default)]
 pub struct Buffer {
     data: Rope,
     cursor: usize,
     cursor_offset: usize,
+
+    /// The caret shape `get_renderables` reports for the cursor.
+    cursor_style: CursorStyle,
+
+    /// Whether the cursor should be drawn this frame. Callers driving a blink timer flip this
+    /// with `toggle_cursor_visible` and redraw; `get_renderables` itself never changes it.
+    cursor_visible: bool,
+
+    /// The previous frame's cells and cursor, used to compute damage on the next
+    /// `get_renderables` call. `None` means there's no previous frame to diff against, so the
+    /// next call reports the whole surface as damaged.
+    previous_frame: Option<PreviousFrame>,
+}
+
+impl Default for Buffer {
+    fn default() -> Self {
+        Self {
+            data: Rope::default(),
+            cursor: 0,
+            cursor_offset: 0,
+            cursor_style: CursorStyle::default(),
+            cursor_visible: true,
+            previous_frame: None,
+        }
+    }
+}
+
+struct PreviousFrame {
+    cells: HashMap<(usize, usize), RenderableCell>,
+    cursor: RenderableCursor,
 }
 
 impl Buffer {
@@ -140,7 +172,18 @@ impl Buffer {
         }
     }
 
-    pub fn get_renderables(&self) -> (Vec<RenderableCell>, RenderableCursor) {
+    /// Renderable cells and cursor for the current frame, plus the pixel-space regions that
+    /// changed since the last call, coalesced into a small set of rectangles suitable for
+    /// `swap_buffers_with_damage`.
+    ///
+    /// When `cursor_style` is [`CursorStyle::Block`] and the cursor is visible, the cell it
+    /// covers has its foreground swapped to its own background so the character stays legible
+    /// drawn on top of the solid cursor block; callers should skip drawing the cursor at all
+    /// when `cursor_visible` is `false` (a blink tick), since this method always returns one.
+    pub fn get_renderables(
+        &mut self,
+        size_info: &SizeInfo,
+    ) -> (Vec<RenderableCell>, RenderableCursor, Vec<DamageRect>) {
         let mut cells = Vec::new();
 
         let s = "Hello world!";
@@ -153,14 +196,113 @@ impl Buffer {
                 fg: Rgb::new(0x33, 0x33, 0x33),
                 bg: Rgb::new(0xfc, 0xfd, 0xfd),
                 underline: Rgb::new(0x33, 0x33, 0x33),
+                underline_style: UnderlineStyle::None,
             };
             cells.push(cell);
         }
 
         let cursor_point = Point::new(10, 3);
-        let cursor = RenderableCursor { point: cursor_point, color: Rgb::new(0x5f, 0xb4, 0xb4) };
+        let cursor = RenderableCursor {
+            point: cursor_point,
+            color: Rgb::new(0x5f, 0xb4, 0xb4),
+            style: self.cursor_style,
+        };
+
+        if self.cursor_visible && cursor.style == CursorStyle::Block {
+            if let Some(cell) = cells
+                .iter_mut()
+                .find(|cell| cell.line == cursor.point.line && cell.column == cursor.point.column)
+            {
+                cell.fg = cell.bg;
+            }
+        }
+
+        let damage = self.compute_damage(&cells, cursor, size_info);
+
+        (cells, cursor, damage)
+    }
+
+    /// The caret shape `get_renderables` draws the cursor with.
+    pub fn cursor_style(&self) -> CursorStyle {
+        self.cursor_style
+    }
+
+    /// Change the caret shape `get_renderables` draws the cursor with.
+    pub fn set_cursor_style(&mut self, style: CursorStyle) {
+        self.cursor_style = style;
+    }
+
+    /// Whether the cursor should be drawn this frame.
+    pub fn cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    /// Flip cursor visibility, for callers driving a blink timer.
+    pub fn toggle_cursor_visible(&mut self) {
+        self.cursor_visible = !self.cursor_visible;
+    }
+
+    /// Force the next `get_renderables` call to report the entire surface as damaged, e.g. after
+    /// a resize or scale-factor change where every cell's pixel position moved.
+    pub fn damage_everything(&mut self) {
+        self.previous_frame = None;
+    }
+
+    /// Diff `cells`/`cursor` against the previous frame, recording this frame as the new
+    /// previous one for next time.
+    fn compute_damage(
+        &mut self,
+        cells: &[RenderableCell],
+        cursor: RenderableCursor,
+        size_info: &SizeInfo,
+    ) -> Vec<DamageRect> {
+        let new_cells: HashMap<(usize, usize), RenderableCell> =
+            cells.iter().map(|cell| ((cell.line, cell.column), cell.clone())).collect();
+
+        let damage = match self.previous_frame.take() {
+            Some(previous) => {
+                let mut rects = Vec::new();
+
+                for (key, cell) in &new_cells {
+                    let unchanged = previous.cells.get(key).is_some_and(|old| {
+                        old.character == cell.character
+                            && old.fg == cell.fg
+                            && old.bg == cell.bg
+                            && old.bg_alpha == cell.bg_alpha
+                            && old.underline == cell.underline
+                            && old.underline_style == cell.underline_style
+                    });
+                    if !unchanged {
+                        rects.push(DamageRect::for_cell(cell.line, cell.column, size_info));
+                    }
+                }
+
+                // A cell that existed last frame but not this one must still be repainted so its
+                // old contents don't linger.
+                for key in previous.cells.keys() {
+                    if !new_cells.contains_key(key) {
+                        rects.push(DamageRect::for_cell(key.0, key.1, size_info));
+                    }
+                }
+
+                // The cursor must be damaged at both its old and new position even when the cell
+                // underneath it didn't change.
+                if previous.cursor != cursor {
+                    let old = previous.cursor.point;
+                    let new = cursor.point;
+                    rects.push(DamageRect::for_cell(old.line, old.column, size_info));
+                    rects.push(DamageRect::for_cell(new.line, new.column, size_info));
+                }
+
+                coalesce_rects(rects)
+            },
+            // No previous frame to diff against: the whole surface is damaged.
+            None => vec![DamageRect::full(size_info)],
+        };
+
+        self.previous_frame = Some(PreviousFrame { cells: new_cells, cursor });
 
-        (cells, cursor)
+        damage
     }
 }
 
@@ -184,6 +326,7 @@ pub enum Movement {
     EndOfLine,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VerticalMovement {
     UpLine,
     DownLine,
@@ -14,6 +14,7 @@ use crate::editor::Editor;
 /// are activated.
 pub struct Processor<A: ActionContext> {
     pub ctx: A,
+    bindings: Vec<Binding>,
 }
 
 pub trait ActionContext {
@@ -29,9 +30,93 @@ pub trait ActionContext {
     fn redraw_editor(&mut self, _window_id: WindowId) {}
 }
 
+/// The effect of a [`Binding`] firing, replacing what used to be a literal expression inline in a
+/// `match` arm.
+#[derive(Debug, Clone, Copy)]
+pub enum Action {
+    MoveCursor(Movement),
+    MoveCursorVertical(VerticalMovement),
+    DeleteCharBackwards,
+    DeleteLineBackwards,
+    InsertNewline,
+    CreateWindow,
+    CloseWindow,
+    Minimize,
+    SelectPreviousTab,
+    SelectNextTab,
+    SelectTab(u8),
+}
+
+impl Action {
+    /// Whether this action operates on window/tab chrome rather than the editor buffer. Those
+    /// actions don't dirty or redraw the buffer the way editing actions do.
+    fn is_window_action(&self) -> bool {
+        matches!(
+            self,
+            Action::CreateWindow
+                | Action::CloseWindow
+                | Action::Minimize
+                | Action::SelectPreviousTab
+                | Action::SelectNextTab
+                | Action::SelectTab(_)
+        )
+    }
+}
+
+/// A keyboard shortcut: the modifiers and key that trigger it, and the [`Action`] it performs.
+#[derive(Debug, Clone)]
+pub struct Binding {
+    pub mods: ModifiersState,
+    pub key: Key<&'static str>,
+    pub action: Action,
+}
+
+impl Binding {
+    pub fn new(mods: ModifiersState, key: Key<&'static str>, action: Action) -> Binding {
+        Binding { mods, key, action }
+    }
+}
+
+/// The bindings `Processor::new` installs, matching this editor's historical hardcoded shortcuts.
+pub fn default_bindings() -> Vec<Binding> {
+    vec![
+        Binding::new(ModifiersState::CONTROL, Key::Character("a"), Action::MoveCursor(Movement::StartOfLine)),
+        Binding::new(ModifiersState::CONTROL, Key::Character("e"), Action::MoveCursor(Movement::EndOfLine)),
+        Binding::new(ModifiersState::SUPER, Key::ArrowLeft, Action::MoveCursor(Movement::StartOfLine)),
+        Binding::new(ModifiersState::SUPER, Key::ArrowRight, Action::MoveCursor(Movement::EndOfLine)),
+        Binding::new(ModifiersState::SUPER, Key::Backspace, Action::DeleteLineBackwards),
+        Binding::new(ModifiersState::empty(), Key::Backspace, Action::DeleteCharBackwards),
+        Binding::new(ModifiersState::empty(), Key::ArrowLeft, Action::MoveCursor(Movement::BackwardChar(1))),
+        Binding::new(ModifiersState::empty(), Key::ArrowRight, Action::MoveCursor(Movement::ForwardChar(1))),
+        Binding::new(ModifiersState::empty(), Key::ArrowUp, Action::MoveCursorVertical(VerticalMovement::UpLine)),
+        Binding::new(ModifiersState::empty(), Key::ArrowDown, Action::MoveCursorVertical(VerticalMovement::DownLine)),
+        Binding::new(ModifiersState::empty(), Key::Enter, Action::InsertNewline),
+        Binding::new(ModifiersState::SUPER, Key::Character("n"), Action::CreateWindow),
+        Binding::new(ModifiersState::SUPER, Key::Character("w"), Action::CloseWindow),
+        Binding::new(ModifiersState::SUPER, Key::Character("m"), Action::Minimize),
+        Binding::new(ModifiersState::SUPER, Key::Character("j"), Action::SelectPreviousTab),
+        Binding::new(ModifiersState::SUPER, Key::Character("k"), Action::SelectNextTab),
+        Binding::new(ModifiersState::SUPER, Key::Character("1"), Action::SelectTab(1)),
+        Binding::new(ModifiersState::SUPER, Key::Character("2"), Action::SelectTab(2)),
+        Binding::new(ModifiersState::SUPER, Key::Character("3"), Action::SelectTab(3)),
+        Binding::new(ModifiersState::SUPER, Key::Character("4"), Action::SelectTab(4)),
+        Binding::new(ModifiersState::SUPER, Key::Character("5"), Action::SelectTab(5)),
+        Binding::new(ModifiersState::SUPER, Key::Character("6"), Action::SelectTab(6)),
+        Binding::new(ModifiersState::SUPER, Key::Character("7"), Action::SelectTab(7)),
+        Binding::new(ModifiersState::SUPER, Key::Character("8"), Action::SelectTab(8)),
+        Binding::new(ModifiersState::SUPER, Key::Character("9"), Action::SelectTab(9)),
+    ]
+}
+
 impl<A: ActionContext> Processor<A> {
     pub fn new(ctx: A) -> Self {
-        Self { ctx }
+        Self::with_bindings(ctx, default_bindings())
+    }
+
+    /// Create a `Processor` with a custom set of keybindings instead of [`default_bindings`], so
+    /// embedders can remap or add shortcuts without recompiling this crate.
+    pub fn with_bindings(ctx: A, bindings: Vec<Binding>) -> Self {
+        Self { ctx, bindings }
     }
 
     /// Modifier state change.
@@ -48,41 +133,20 @@ impl<A: ActionContext> Processor<A> {
 
         let text = key.text_with_all_modifiers().unwrap_or_default();
 
-        // Key bindings suppress the character input.
-        if self.process_key_bindings(&key) {
-            return;
-        }
+        if let Some(action) = self.lookup_binding(&key) {
+            self.perform_action(action);
 
-        if text.is_empty() {
-            return;
-        }
+            // Window/tab actions suppress the character input and don't redraw the buffer.
+            if action.is_window_action() {
+                return;
+            }
+        } else {
+            if text.is_empty() {
+                return;
+            }
 
-        let mods = self.ctx.modifiers().state();
-        let editor = &mut self.ctx.editor_mut();
-        match (mods, key.key_without_modifiers().as_ref()) {
-            (ModifiersState::CONTROL, Key::Character("a")) => {
-                editor.buffer_mut().move_cursor(Movement::StartOfLine)
-            },
-            (ModifiersState::CONTROL, Key::Character("e")) => {
-                editor.buffer_mut().move_cursor(Movement::EndOfLine)
-            },
-            (ModifiersState::SUPER, Key::ArrowLeft) => {
-                editor.buffer_mut().move_cursor(Movement::StartOfLine)
-            },
-            (ModifiersState::SUPER, Key::ArrowRight) => {
-                editor.buffer_mut().move_cursor(Movement::EndOfLine)
-            },
-            (ModifiersState::SUPER, Key::Backspace) => editor.buffer_mut().delete_line_backwards(),
-            (_, Key::Backspace) => editor.buffer_mut().delete_char_backwards(),
-            (_, Key::ArrowLeft) => editor.buffer_mut().move_cursor(Movement::BackwardChar(1)),
-            (_, Key::ArrowRight) => editor.buffer_mut().move_cursor(Movement::ForwardChar(1)),
-            (_, Key::ArrowUp) => editor.buffer_mut().move_cursor_vertical(VerticalMovement::UpLine),
-            (_, Key::ArrowDown) => {
-                editor.buffer_mut().move_cursor_vertical(VerticalMovement::DownLine)
-            },
-            (_, Key::Enter) => editor.buffer_mut().insert("\n"),
-            (_, _) => editor.buffer_mut().insert(text),
-        };
+            self.ctx.editor_mut().buffer_mut().insert(text);
+        }
 
         self.ctx.mark_dirty();
 
@@ -90,33 +154,35 @@ impl<A: ActionContext> Processor<A> {
         self.ctx.redraw_editor(window_id);
     }
 
-    fn process_key_bindings(&mut self, key: &KeyEvent) -> bool {
+    /// Find the binding, if any, matching the current modifiers and `key`.
+    fn lookup_binding(&mut self, key: &KeyEvent) -> Option<Action> {
         let mods = self.ctx.modifiers().state();
+        let pressed_key = key.key_without_modifiers();
 
-        // Don't suppress char if no bindings were triggered.
-        let mut suppress_chars = true;
+        self.bindings
+            .iter()
+            .find(|binding| binding.mods == mods && binding.key == pressed_key.as_ref())
+            .map(|binding| binding.action)
+    }
 
-        match (mods, key.key_without_modifiers().as_ref()) {
-            (ModifiersState::SUPER, Key::Character("n")) => self.ctx.create_new_window(),
-            (ModifiersState::SUPER, Key::Character("w")) => {
+    fn perform_action(&mut self, action: Action) {
+        match action {
+            Action::MoveCursor(movement) => self.ctx.editor_mut().buffer_mut().move_cursor(movement),
+            Action::MoveCursorVertical(movement) => {
+                self.ctx.editor_mut().buffer_mut().move_cursor_vertical(movement)
+            },
+            Action::DeleteCharBackwards => self.ctx.editor_mut().buffer_mut().delete_char_backwards(),
+            Action::DeleteLineBackwards => self.ctx.editor_mut().buffer_mut().delete_line_backwards(),
+            Action::InsertNewline => self.ctx.editor_mut().buffer_mut().insert("\n"),
+            Action::CreateWindow => self.ctx.create_new_window(),
+            Action::CloseWindow => {
                 let window_id = self.ctx.window().id();
                 self.ctx.close_window(window_id);
             },
-            (ModifiersState::SUPER, Key::Character("m")) => self.ctx.window().set_minimized(true),
-            (ModifiersState::SUPER, Key::Character("j")) => self.ctx.window().select_previous_tab(),
-            (ModifiersState::SUPER, Key::Character("k")) => self.ctx.window().select_next_tab(),
-            (ModifiersState::SUPER, Key::Character("1")) => self.ctx.window().select_tab(1),
-            (ModifiersState::SUPER, Key::Character("2")) => self.ctx.window().select_tab(2),
-            (ModifiersState::SUPER, Key::Character("3")) => self.ctx.window().select_tab(3),
-            (ModifiersState::SUPER, Key::Character("4")) => self.ctx.window().select_tab(4),
-            (ModifiersState::SUPER, Key::Character("5")) => self.ctx.window().select_tab(5),
-            (ModifiersState::SUPER, Key::Character("6")) => self.ctx.window().select_tab(6),
-            (ModifiersState::SUPER, Key::Character("7")) => self.ctx.window().select_tab(7),
-            (ModifiersState::SUPER, Key::Character("8")) => self.ctx.window().select_tab(8),
-            (ModifiersState::SUPER, Key::Character("9")) => self.ctx.window().select_tab(9),
-            (_, _) => suppress_chars = false,
-        };
-
-        suppress_chars
+            Action::Minimize => self.ctx.window().set_minimized(true),
+            Action::SelectPreviousTab => self.ctx.window().select_previous_tab(),
+            Action::SelectNextTab => self.ctx.window().select_next_tab(),
+            Action::SelectTab(n) => self.ctx.window().select_tab(n),
+        }
     }
 }
@@ -12,13 +12,121 @@ use crate::gl;
 use crate::gl::types::*;
 
 mod atlas;
+pub mod debug_overlay;
+mod glyph_cache;
+pub mod hot_reload;
 pub mod platform;
+pub mod rects;
+pub mod render_thread;
 
-use atlas::{Atlas, ATLAS_SIZE};
+use atlas::{Atlas, AtlasSet, Glyph, LoadGlyph, ATLAS_SIZE};
 
 /// Maximum items to be drawn in a batch.
 const BATCH_MAX: usize = 0x1_0000;
 
+/// Picks between the default core-profile GL 3.3 renderer and a GLES2 fallback, so the crate
+/// keeps rendering on older/embedded GPUs (Raspberry Pi, phones, some VMs) that only expose
+/// OpenGL ES 2.0.
+#[derive(Debug)]
+pub enum TextRenderer {
+    Gl3(Glsl3Renderer),
+    Gles2(Gles2Renderer),
+}
+
+impl TextRenderer {
+    /// Query the context's reported GL version and construct whichever backend it supports.
+    pub fn new(
+        context: &PossiblyCurrentContext,
+        rasterizer: &mut Rasterizer,
+    ) -> Result<Self, Error> {
+        let gl_display = context.display();
+        if gl_display.version_string().contains("OpenGL ES") {
+            Ok(Self::Gles2(Gles2Renderer::new(context)?))
+        } else {
+            Ok(Self::Gl3(Glsl3Renderer::new(context, rasterizer)?))
+        }
+    }
+
+    pub fn draw_cells(
+        &mut self,
+        size_info: &SizeInfo,
+        rasterizer: &mut Rasterizer,
+        font_key: FontKey,
+        font_size: Size,
+    ) {
+        match self {
+            Self::Gl3(renderer) => renderer.draw_cells(size_info, rasterizer, font_key, font_size),
+            Self::Gles2(renderer) => {
+                renderer.draw_cells(size_info, rasterizer, font_key, font_size)
+            },
+        }
+    }
+
+    pub fn resize(&self, size: &SizeInfo) {
+        match self {
+            Self::Gl3(renderer) => renderer.resize(size),
+            Self::Gles2(renderer) => renderer.resize(size),
+        }
+    }
+
+    /// Called once per frame by the render thread: recompiles the text shader from disk if the
+    /// `live-shader-reload` feature is on and a watched file changed. No-op on the GLES2 path,
+    /// which doesn't wire up a watcher.
+    pub fn reload_shaders_if_changed(&mut self) {
+        if let Self::Gl3(renderer) = self {
+            renderer.reload_shaders_if_changed();
+        }
+    }
+
+    /// Fraction of the active atlas page's vertical space consumed so far; for the debug overlay.
+    pub fn atlas_occupancy(&self) -> f32 {
+        match self {
+            Self::Gl3(renderer) => renderer.atlases.occupancy(),
+            Self::Gles2(renderer) => renderer.atlas.occupancy(),
+        }
+    }
+
+    /// Number of glyphs packed into every atlas page so far; for the debug overlay.
+    pub fn atlas_glyph_count(&self) -> u32 {
+        match self {
+            Self::Gl3(renderer) => renderer.atlases.glyph_count(),
+            Self::Gles2(renderer) => renderer.atlas.glyph_count(),
+        }
+    }
+
+    /// Drop the active atlas (or atlases) and start fresh, e.g. after a DPI change where glyphs
+    /// rasterized at the old scale factor must not be reused.
+    pub fn clear_atlas(&mut self) {
+        match self {
+            Self::Gl3(renderer) => {
+                renderer.atlases.clear();
+                renderer.glyph_cache.clear();
+            },
+            Self::Gles2(renderer) => renderer.atlas = Atlas::new(ATLAS_SIZE),
+        }
+    }
+
+    /// Bound how many distinct glyphs the glyph cache keeps at once. Evicted glyphs' atlas regions
+    /// are not reclaimed (`AtlasSet` keeps the no-op default `unload_glyph`), so this does not cap
+    /// the memory `atlases` pages use. No-op on the GLES2 path, which has no glyph cache in front
+    /// of its single atlas.
+    pub fn set_glyph_cache_capacity(&mut self, capacity: usize) {
+        if let Self::Gl3(renderer) = self {
+            renderer.glyph_cache.set_capacity(capacity, &mut renderer.atlases);
+        }
+    }
+
+    /// Multiply every cell's background alpha by `opacity` (`0.0`-`1.0`), so a transparent window
+    /// lets the desktop show through background-only regions. No-op on the GLES2 path, which
+    /// draws no background at all. Callers creating a transparent window should pass the same
+    /// value used to pick an alpha-backed framebuffer config.
+    pub fn set_background_opacity(&mut self, opacity: f32) {
+        if let Self::Gl3(renderer) = self {
+            renderer.background_opacity = opacity;
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Glsl3Renderer {
     shader_program: GLuint,
@@ -27,14 +135,31 @@ pub struct Glsl3Renderer {
     vao: GLuint,
     ebo: GLuint,
     vbo_instance: GLuint,
-    atlas: Atlas,
+
+    atlases: AtlasSet,
     active_tex: GLuint,
-    tex: GLuint,
     instances: Vec<InstanceData>,
+
+    /// Caches rasterized glyphs (keyed by subpixel phase) in front of `atlases`, so redrawing
+    /// the same text every frame doesn't re-rasterize and re-upload it.
+    glyph_cache: glyph_cache::GlyphCache,
+
+    /// Watches `res/text.{v,f}.glsl` for changes under the `live-shader-reload` feature; `None`
+    /// when the feature is off.
+    watcher: Option<hot_reload::ShaderWatcher>,
+
+    /// Multiplied into every cell's background alpha, so a single knob can fade the whole
+    /// background toward transparent (see [`TextRenderer::set_background_opacity`]) without
+    /// touching individual cell data. `1.0` (fully opaque) unless the window itself was created
+    /// with a transparent, alpha-backed framebuffer.
+    background_opacity: f32,
 }
 
 impl Glsl3Renderer {
-    pub fn new(context: &PossiblyCurrentContext) -> Self {
+    pub fn new(
+        context: &PossiblyCurrentContext,
+        rasterizer: &mut Rasterizer,
+    ) -> Result<Self, Error> {
         let gl_display = context.display();
         gl::load_with(|symbol| {
             let symbol = CString::new(symbol).unwrap();
@@ -129,6 +254,63 @@ impl Glsl3Renderer {
             size += 4 * size_of::<f32>();
             index += 1;
 
+            // Text color.
+            gl::VertexAttribPointer(
+                index,
+                3,
+                gl::UNSIGNED_BYTE,
+                gl::TRUE,
+                size_of::<InstanceData>() as i32,
+                size as *const _,
+            );
+            gl::EnableVertexAttribArray(index);
+            gl::VertexAttribDivisor(index, 1);
+
+            size += 3 * size_of::<u8>();
+            index += 1;
+
+            // Colored flag.
+            gl::VertexAttribIPointer(
+                index,
+                1,
+                gl::UNSIGNED_BYTE,
+                size_of::<InstanceData>() as i32,
+                size as *const _,
+            );
+            gl::EnableVertexAttribArray(index);
+            gl::VertexAttribDivisor(index, 1);
+
+            size += size_of::<u8>();
+            index += 1;
+
+            // Background color + alpha.
+            gl::VertexAttribPointer(
+                index,
+                4,
+                gl::UNSIGNED_BYTE,
+                gl::TRUE,
+                size_of::<InstanceData>() as i32,
+                size as *const _,
+            );
+            gl::EnableVertexAttribArray(index);
+            gl::VertexAttribDivisor(index, 1);
+
+            size += 4 * size_of::<u8>();
+            index += 1;
+
+            // Background flag: paint this instance solid with its background color instead of
+            // sampling the atlas, for the full-cell quad `RenderApi::draw_background` queues
+            // underneath each cell's glyph.
+            gl::VertexAttribIPointer(
+                index,
+                1,
+                gl::UNSIGNED_BYTE,
+                size_of::<InstanceData>() as i32,
+                size as *const _,
+            );
+            gl::EnableVertexAttribArray(index);
+            gl::VertexAttribDivisor(index, 1);
+
             // Cleanup.
             gl::BindVertexArray(0);
             gl::BindBuffer(gl::ARRAY_BUFFER, 0);
@@ -142,41 +324,93 @@ impl Glsl3Renderer {
             };
         }
 
-        unsafe {
-            let shader_program = gl::CreateProgram();
-            let vertex_shader = Shader::new(gl::VERTEX_SHADER, include_str!("../res/text.v.glsl"));
+        let shader_program = unsafe {
+            let vertex_shader = Shader::new(gl::VERTEX_SHADER, include_str!("../res/text.v.glsl"))?;
             let fragment_shader =
-                Shader::new(gl::FRAGMENT_SHADER, include_str!("../res/text.f.glsl"));
+                Shader::new(gl::FRAGMENT_SHADER, include_str!("../res/text.f.glsl"))?;
 
-            gl::AttachShader(shader_program, vertex_shader.0);
-            gl::AttachShader(shader_program, fragment_shader.0);
-            gl::LinkProgram(shader_program);
+            link_program(vertex_shader.0, fragment_shader.0)?
+        };
 
+        unsafe {
             let u_resolution = gl::GetUniformLocation(shader_program, cstr!("resolution").as_ptr());
             let u_cell_dim = gl::GetUniformLocation(shader_program, cstr!("cellDim").as_ptr());
 
-            Self {
+            let watcher = hot_reload::ShaderWatcher::new(&[
+                hot_reload::res_path("text.v.glsl"),
+                hot_reload::res_path("text.f.glsl"),
+            ]);
+
+            Ok(Self {
                 shader_program,
                 u_resolution,
                 u_cell_dim,
                 vao,
                 ebo,
                 vbo_instance,
-                atlas: Atlas::new(ATLAS_SIZE),
+                atlases: AtlasSet::new(),
                 active_tex: 0,
-                tex: 0,
                 instances: Vec::new(),
-            }
+                glyph_cache: glyph_cache::GlyphCache::new(rasterizer)?,
+                watcher,
+                background_opacity: 1.0,
+            })
         }
     }
 
-    pub fn draw_cells(
-        &mut self,
-        size_info: &SizeInfo,
-        rasterizer: &mut Rasterizer,
-        font_key: FontKey,
-        font_size: Size,
-    ) {
+    /// Called once per frame by the render thread: if a watched shader changed on disk, recompile
+    /// the program and swap it in, re-querying uniform locations, and keeping the previously working
+    /// program on failure instead of leaving the renderer without one.
+    pub fn reload_shaders_if_changed(&mut self) {
+        let Some(watcher) = &self.watcher else { return };
+        if !watcher.poll_changed() {
+            return;
+        }
+
+        let Some(vertex) = hot_reload::read_source(&hot_reload::res_path("text.v.glsl")) else {
+            return;
+        };
+        let Some(fragment) = hot_reload::read_source(&hot_reload::res_path("text.f.glsl")) else {
+            return;
+        };
+
+        let program = match Self::try_link(vertex, fragment) {
+            Ok(program) => program,
+            Err(err) => {
+                println!("failed to recompile text shader, keeping previous program: {err}");
+                return;
+            },
+        };
+
+        macro_rules! cstr {
+            ($s:literal) => {
+                std::ffi::CStr::from_ptr(concat!($s, "\0").as_ptr().cast())
+            };
+        }
+
+        unsafe {
+            gl::DeleteProgram(self.shader_program);
+
+            self.shader_program = program;
+            self.u_resolution = gl::GetUniformLocation(program, cstr!("resolution").as_ptr());
+            self.u_cell_dim = gl::GetUniformLocation(program, cstr!("cellDim").as_ptr());
+        }
+    }
+
+    /// Compile and link `vertex`/`fragment` into a fresh program, so the caller can keep the
+    /// previous program on failure instead of swapping in a broken one.
+    fn try_link(vertex: &'static str, fragment: &'static str) -> Result<GLuint, Error> {
+        unsafe {
+            let vertex_shader = Shader::new(gl::VERTEX_SHADER, vertex)?;
+            let fragment_shader = Shader::new(gl::FRAGMENT_SHADER, fragment)?;
+            link_program(vertex_shader.0, fragment_shader.0)
+        }
+    }
+
+    /// Bind the program, VAO, and buffers a batch of [`RenderApi::draw_glyph`] calls needs, and
+    /// return a handle that flushes whatever's left queued when it's dropped -- callers can't
+    /// forget the final draw the way the old hand-rolled `draw_cells` loop could.
+    fn with_api(&mut self, size_info: &SizeInfo) -> RenderApi<'_> {
         unsafe {
             gl::UseProgram(self.shader_program);
             gl::Uniform2f(self.u_cell_dim, size_info.cell_width, size_info.cell_height);
@@ -187,6 +421,20 @@ impl Glsl3Renderer {
             gl::ActiveTexture(gl::TEXTURE0);
         }
 
+        RenderApi { renderer: self }
+    }
+
+    pub fn draw_cells(
+        &mut self,
+        size_info: &SizeInfo,
+        rasterizer: &mut Rasterizer,
+        _font_key: FontKey,
+        _font_size: Size,
+    ) {
+        let font_key = self.glyph_cache.font_key;
+        let font_size = self.glyph_cache.font_size;
+        let mut api = self.with_api(size_info);
+
         let strs = vec![
             "E",
             "Hello world!",
@@ -210,34 +458,32 @@ impl Glsl3Renderer {
             "Hello world!",
         ];
 
+        let cell_width = size_info.cell_width as i16;
+        let cell_height = size_info.cell_height as i16;
+
         for (i, s) in strs.iter().enumerate() {
             for (column, character) in s.chars().enumerate() {
                 let line = 10 + i;
+                let pen_x = column as f32 * size_info.cell_width;
+
+                api.draw_background(column as u16, line as u16, cell_width, cell_height, [
+                    0, 0, 0,
+                ]);
 
                 let glyph_key = GlyphKey { font_key, size: font_size, character };
-                let rasterized = rasterizer.get_glyph(glyph_key).unwrap();
-                let glyph = self.atlas.insert_inner(&rasterized);
-
-                if self.instances.len() == 0 {
-                    self.tex = glyph.tex_id;
-                }
-
-                self.instances.push(InstanceData {
-                    col: column as u16,
-                    row: line as u16,
-
-                    top: glyph.top,
-                    left: glyph.left,
-                    width: glyph.width,
-                    height: glyph.height,
-
-                    uv_bot: glyph.uv_bot,
-                    uv_left: glyph.uv_left,
-                    uv_width: glyph.uv_width,
-                    uv_height: glyph.uv_height,
-                });
+                api.draw_glyph(rasterizer, column as u16, line as u16, glyph_key, pen_x, [
+                    255, 255, 255,
+                ]);
             }
         }
+    }
+
+    /// Upload and draw the queued instances against `self.active_tex`, then clear the batch.
+    /// No-op when nothing is queued.
+    fn flush_batch(&mut self) {
+        if self.instances.is_empty() {
+            return;
+        }
 
         unsafe {
             gl::BufferSubData(
@@ -247,11 +493,7 @@ impl Glsl3Renderer {
                 self.instances.as_ptr() as *const _,
             );
 
-            // Bind texture if necessary.
-            // if self.active_tex != self.tex {
-            gl::BindTexture(gl::TEXTURE_2D, self.tex);
-            //     self.active_tex = self.tex;
-            // }
+            gl::BindTexture(gl::TEXTURE_2D, self.active_tex);
 
             gl::DrawElementsInstanced(
                 gl::TRIANGLES,
@@ -261,6 +503,8 @@ impl Glsl3Renderer {
                 self.instances.len() as GLsizei,
             );
         }
+
+        self.instances.clear();
     }
 
     pub fn resize(&self, size: &SizeInfo) {
@@ -274,10 +518,376 @@ impl Glsl3Renderer {
     }
 }
 
+/// Handle returned by [`Glsl3Renderer::with_api`]: binds the renderer's program/VAO/buffers on
+/// creation and guarantees, via [`Drop`], that whatever instances are still queued get flushed
+/// before the handle goes away.
+struct RenderApi<'a> {
+    renderer: &'a mut Glsl3Renderer,
+}
+
+impl RenderApi<'_> {
+    /// Look `glyph_key` up in the glyph cache (rasterizing and uploading it to the atlas on a
+    /// miss), queue its instance, and flush the batch first if either the atlas texture changed
+    /// or the instance buffer is full.
+    fn draw_glyph(
+        &mut self,
+        rasterizer: &mut Rasterizer,
+        col: u16,
+        row: u16,
+        glyph_key: GlyphKey,
+        pen_x: f32,
+        fg: [u8; 3],
+    ) {
+        let Glsl3Renderer { glyph_cache, atlases, .. } = &mut *self.renderer;
+        let glyph = glyph_cache.get(glyph_key, pen_x, fg, rasterizer, atlases);
+
+        if !self.renderer.instances.is_empty() && glyph.tex_id != self.renderer.active_tex {
+            self.renderer.flush_batch();
+        }
+
+        if self.renderer.instances.is_empty() {
+            self.renderer.active_tex = glyph.tex_id;
+        }
+
+        self.renderer.instances.push(InstanceData {
+            col,
+            row,
+
+            top: glyph.top,
+            left: glyph.left,
+            width: glyph.width,
+            height: glyph.height,
+
+            uv_bot: glyph.uv_bot,
+            uv_left: glyph.uv_left,
+            uv_width: glyph.uv_width,
+            uv_height: glyph.uv_height,
+
+            fg,
+            colored: glyph.colored as u8,
+
+            bg: [0, 0, 0, 0],
+            background: 0,
+        });
+
+        if self.renderer.instances.len() == BATCH_MAX {
+            self.renderer.flush_batch();
+        }
+    }
+
+    /// Queue a full-cell solid-fill quad at `(col, row)` underneath that cell's glyph, painted
+    /// with `bg` scaled by [`Glsl3Renderer::background_opacity`] rather than sampled from the
+    /// atlas, so a background alpha below full opacity lets the desktop show through a
+    /// transparent window.
+    fn draw_background(
+        &mut self,
+        col: u16,
+        row: u16,
+        cell_width: i16,
+        cell_height: i16,
+        bg: [u8; 3],
+    ) {
+        let bg_a = (self.renderer.background_opacity * 255.0).round() as u8;
+
+        self.renderer.instances.push(InstanceData {
+            col,
+            row,
+
+            top: cell_height,
+            left: 0,
+            width: cell_width,
+            height: cell_height,
+
+            uv_bot: 0.,
+            uv_left: 0.,
+            uv_width: 0.,
+            uv_height: 0.,
+
+            fg: [0, 0, 0],
+            colored: 0,
+
+            bg: [bg[0], bg[1], bg[2], bg_a],
+            background: 1,
+        });
+
+        if self.renderer.instances.len() == BATCH_MAX {
+            self.renderer.flush_batch();
+        }
+    }
+}
+
+impl Drop for RenderApi<'_> {
+    fn drop(&mut self) {
+        self.renderer.flush_batch();
+    }
+}
+
+/// GLES2 fallback renderer. GLES 2.0 has no VAOs, so attribute bindings are re-applied on every
+/// [`Self::draw_cells`] instead of being cached once at construction; it also has no attribute
+/// divisors or `GL_ARB_blend_func_extended`, so instancing and the dual-source blend trick the GL
+/// 3.3 renderer relies on are both emulated instead of used directly (see [`Gles2Vertex`] and the
+/// two-pass draw below). Shader sources are the `attribute`/`varying`-dialect variants loaded from
+/// `res/gles2/`.
+#[derive(Debug)]
+pub struct Gles2Renderer {
+    shader_program: GLuint,
+    u_resolution: GLint,
+    u_cell_dim: GLint,
+    u_fg: GLint,
+    u_pass: GLint,
+    a_corner: GLint,
+    a_coords: GLint,
+    a_glyph_offset_size: GLint,
+    a_uv: GLint,
+    vbo: GLuint,
+    atlas: Atlas,
+    tex: GLuint,
+    vertices: Vec<Gles2Vertex>,
+}
+
+impl Gles2Renderer {
+    fn new(context: &PossiblyCurrentContext) -> Result<Self, Error> {
+        let gl_display = context.display();
+        gl::load_with(|symbol| {
+            let symbol = CString::new(symbol).unwrap();
+            gl_display.get_proc_address(symbol.as_c_str()).cast()
+        });
+
+        let mut vbo: GLuint = 0;
+
+        unsafe {
+            gl::Enable(gl::BLEND);
+            // Normal alpha blending; the two text passes below swap this out for the duration of
+            // `draw_cells` and restore it afterwards so the rect renderer isn't affected.
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            gl::DepthMask(gl::FALSE);
+
+            gl::GenBuffers(1, &mut vbo);
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (BATCH_MAX * 6 * size_of::<Gles2Vertex>()) as isize,
+                ptr::null(),
+                gl::STREAM_DRAW,
+            );
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+
+        let vertex_shader =
+            unsafe { Shader::new(gl::VERTEX_SHADER, include_str!("../res/gles2/text.v.glsl"))? };
+        let fragment_shader = unsafe {
+            Shader::new(gl::FRAGMENT_SHADER, include_str!("../res/gles2/text.f.glsl"))?
+        };
+        let shader_program = unsafe { link_program(vertex_shader.0, fragment_shader.0)? };
+
+        let (u_resolution, u_cell_dim, u_fg, u_pass, a_corner, a_coords, a_glyph_offset_size, a_uv) =
+            unsafe {
+            macro_rules! cstr {
+                ($s:literal) => {
+                    std::ffi::CStr::from_ptr(concat!($s, "\0").as_ptr().cast())
+                };
+            }
+
+            (
+                gl::GetUniformLocation(shader_program, cstr!("resolution").as_ptr()),
+                gl::GetUniformLocation(shader_program, cstr!("cellDim").as_ptr()),
+                gl::GetUniformLocation(shader_program, cstr!("uFg").as_ptr()),
+                gl::GetUniformLocation(shader_program, cstr!("uPass").as_ptr()),
+                gl::GetAttribLocation(shader_program, cstr!("aCorner").as_ptr()),
+                gl::GetAttribLocation(shader_program, cstr!("aCoords").as_ptr()),
+                gl::GetAttribLocation(shader_program, cstr!("aGlyphOffsetSize").as_ptr()),
+                gl::GetAttribLocation(shader_program, cstr!("aUv").as_ptr()),
+            )
+        };
+
+        Ok(Self {
+            shader_program,
+            u_resolution,
+            u_cell_dim,
+            u_fg,
+            u_pass,
+            a_corner,
+            a_coords,
+            a_glyph_offset_size,
+            a_uv,
+            vbo,
+            atlas: Atlas::new(ATLAS_SIZE),
+            tex: 0,
+            vertices: Vec::new(),
+        })
+    }
+
+    /// Expand one glyph instance into the 6 vertices (2 triangles, two corners repeated) GLES2
+    /// draws in place of an instanced draw, tagging each with the corner of the glyph quad it
+    /// represents.
+    fn push_glyph(vertices: &mut Vec<Gles2Vertex>, col: u16, row: u16, glyph: &Glyph) {
+        let base = Gles2Vertex {
+            corner: [0.0, 0.0],
+            coords: [col, row],
+            glyph_offset_size: [glyph.left, glyph.top, glyph.width, glyph.height],
+            uv: [glyph.uv_left, glyph.uv_bot, glyph.uv_width, glyph.uv_height],
+        };
+
+        let top_left = Gles2Vertex { corner: [0.0, 0.0], ..base };
+        let bottom_left = Gles2Vertex { corner: [0.0, 1.0], ..base };
+        let top_right = Gles2Vertex { corner: [1.0, 0.0], ..base };
+        let bottom_right = Gles2Vertex { corner: [1.0, 1.0], ..base };
+
+        vertices.push(top_left);
+        vertices.push(bottom_left);
+        vertices.push(top_right);
+        vertices.push(top_right);
+        vertices.push(bottom_right);
+        vertices.push(bottom_left);
+    }
+
+    /// Re-apply the vertex attribute bindings for the currently-bound VBO; GLES2 has no VAOs to
+    /// cache this state in, so it's redone on every draw.
+    fn bind_attributes(&self) {
+        unsafe {
+            let mut offset = 0;
+
+            gl::VertexAttribPointer(
+                self.a_corner as GLuint,
+                2,
+                gl::FLOAT,
+                gl::FALSE,
+                size_of::<Gles2Vertex>() as i32,
+                offset as *const _,
+            );
+            gl::EnableVertexAttribArray(self.a_corner as GLuint);
+            offset += 2 * size_of::<f32>();
+
+            gl::VertexAttribPointer(
+                self.a_coords as GLuint,
+                2,
+                gl::UNSIGNED_SHORT,
+                gl::FALSE,
+                size_of::<Gles2Vertex>() as i32,
+                offset as *const _,
+            );
+            gl::EnableVertexAttribArray(self.a_coords as GLuint);
+            offset += 2 * size_of::<u16>();
+
+            gl::VertexAttribPointer(
+                self.a_glyph_offset_size as GLuint,
+                4,
+                gl::SHORT,
+                gl::FALSE,
+                size_of::<Gles2Vertex>() as i32,
+                offset as *const _,
+            );
+            gl::EnableVertexAttribArray(self.a_glyph_offset_size as GLuint);
+            offset += 4 * size_of::<i16>();
+
+            gl::VertexAttribPointer(
+                self.a_uv as GLuint,
+                4,
+                gl::FLOAT,
+                gl::FALSE,
+                size_of::<Gles2Vertex>() as i32,
+                offset as *const _,
+            );
+            gl::EnableVertexAttribArray(self.a_uv as GLuint);
+        }
+    }
+
+    pub fn draw_cells(
+        &mut self,
+        _size_info: &SizeInfo,
+        rasterizer: &mut Rasterizer,
+        font_key: FontKey,
+        font_size: Size,
+    ) {
+        self.vertices.clear();
+
+        let glyph_key = GlyphKey { font_key, size: font_size, character: 'E' };
+        let rasterized = rasterizer.get_glyph(glyph_key).unwrap();
+        let glyph = self.atlas.insert_inner(&rasterized);
+        self.tex = glyph.tex_id;
+
+        Self::push_glyph(&mut self.vertices, 0, 0, &glyph);
+
+        unsafe {
+            gl::UseProgram(self.shader_program);
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            self.bind_attributes();
+
+            gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                0,
+                (self.vertices.len() * size_of::<Gles2Vertex>()) as isize,
+                self.vertices.as_ptr() as *const _,
+            );
+
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.tex);
+
+            // Pass 0: darken the destination by the inverse coverage.
+            gl::Uniform1i(self.u_pass, 0);
+            gl::BlendFunc(gl::ZERO, gl::ONE_MINUS_SRC_COLOR);
+            gl::DrawArrays(gl::TRIANGLES, 0, self.vertices.len() as GLsizei);
+
+            // Pass 1: add the tinted coverage on top.
+            gl::Uniform1i(self.u_pass, 1);
+            gl::Uniform3f(self.u_fg, 1.0, 1.0, 1.0);
+            gl::BlendFunc(gl::ONE, gl::ONE);
+            gl::DrawArrays(gl::TRIANGLES, 0, self.vertices.len() as GLsizei);
+
+            // Restore the default blend func for whatever draws next (e.g. `RectRenderer`).
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+    }
+
+    pub fn resize(&self, size: &SizeInfo) {
+        unsafe {
+            gl::Viewport(0, 0, size.width as i32, size.height as i32);
+
+            gl::UseProgram(self.shader_program);
+            gl::Uniform2f(self.u_resolution, size.width, size.height);
+            gl::UseProgram(0);
+        }
+    }
+}
+
+/// Failure constructing a [`TextRenderer`]: a shader failed to compile, the program it was
+/// attached to failed to link, or the glyph cache's font couldn't be loaded/measured. The shader
+/// variants' strings hold the driver's info log.
+#[derive(Debug)]
+pub enum Error {
+    ShaderCompile(String),
+    ShaderLink(String),
+    Font(crossfont::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::ShaderCompile(log) => write!(f, "shader compilation failed:\n{log}"),
+            Error::ShaderLink(log) => write!(f, "program linking failed:\n{log}"),
+            Error::Font(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<crossfont::Error> for Error {
+    fn from(err: crossfont::Error) -> Self {
+        Error::Font(err)
+    }
+}
+
 struct Shader(GLuint);
 
 impl Shader {
-    fn new(kind: GLenum, source: &'static str) -> Self {
+    /// Compile `source` as a shader of `kind`, returning [`Error::ShaderCompile`] with the
+    /// driver's info log if compilation fails.
+    fn new(kind: GLenum, source: &'static str) -> Result<Self, Error> {
         let mut sources = Vec::<*const GLchar>::with_capacity(3);
         let mut lengthes = Vec::<GLint>::with_capacity(3);
 
@@ -294,12 +904,67 @@ impl Shader {
                 lengthes.as_ptr(),
             );
             gl::CompileShader(shader.0);
+
+            let mut success: GLint = 0;
+            gl::GetShaderiv(shader.0, gl::COMPILE_STATUS, &mut success);
+            if success == gl::FALSE as GLint {
+                return Err(Error::ShaderCompile(shader_info_log(shader.0)));
+            }
         }
 
-        shader
+        Ok(shader)
     }
 }
 
+/// Retrieve a compiled shader's info log, e.g. after [`gl::GetShaderiv`] reports
+/// `GL_COMPILE_STATUS == GL_FALSE`.
+unsafe fn shader_info_log(shader: GLuint) -> String {
+    let mut len: GLint = 0;
+    gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut len);
+    if len <= 0 {
+        return String::new();
+    }
+
+    let mut buf = vec![0u8; len as usize];
+    gl::GetShaderInfoLog(shader, len, ptr::null_mut(), buf.as_mut_ptr().cast());
+    buf.truncate(buf.len().saturating_sub(1));
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+/// Retrieve a linked program's info log, e.g. after [`gl::GetProgramiv`] reports
+/// `GL_LINK_STATUS == GL_FALSE`.
+unsafe fn program_info_log(program: GLuint) -> String {
+    let mut len: GLint = 0;
+    gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut len);
+    if len <= 0 {
+        return String::new();
+    }
+
+    let mut buf = vec![0u8; len as usize];
+    gl::GetProgramInfoLog(program, len, ptr::null_mut(), buf.as_mut_ptr().cast());
+    buf.truncate(buf.len().saturating_sub(1));
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+/// Attach `vertex`/`fragment` to a fresh program and link it, returning
+/// [`Error::ShaderLink`] with the driver's info log if linking fails.
+unsafe fn link_program(vertex: GLuint, fragment: GLuint) -> Result<GLuint, Error> {
+    let program = gl::CreateProgram();
+    gl::AttachShader(program, vertex);
+    gl::AttachShader(program, fragment);
+    gl::LinkProgram(program);
+
+    let mut success: GLint = 0;
+    gl::GetProgramiv(program, gl::LINK_STATUS, &mut success);
+    if success == gl::FALSE as GLint {
+        let log = program_info_log(program);
+        gl::DeleteProgram(program);
+        return Err(Error::ShaderLink(log));
+    }
+
+    Ok(program)
+}
+
 #[derive(Debug)]
 #[repr(C)]
 pub struct InstanceData {
@@ -318,4 +983,37 @@ pub struct InstanceData {
     pub uv_bot: f32,
     pub uv_width: f32,
     pub uv_height: f32,
+
+    // Foreground color.
+    pub fg: [u8; 3],
+
+    // Whether the glyph's texture holds real color (e.g. emoji) rather than a coverage mask.
+    pub colored: u8,
+
+    // Cell background color and alpha, already scaled by `Glsl3Renderer::background_opacity`.
+    pub bg: [u8; 4],
+
+    // Paint this instance solid with `bg` instead of sampling the atlas; set on the full-cell
+    // quad `RenderApi::draw_background` queues underneath each cell's glyph instance(s).
+    pub background: u8,
+}
+
+/// Per-vertex data for [`Gles2Renderer`]. GLES2 has no attribute divisors, so instead of one
+/// `InstanceData` shared across a drawn-by-reference quad, each glyph is expanded on the CPU into
+/// 6 of these -- two triangles, each vertex tagged with its quad corner via `corner` -- and drawn
+/// with a plain [`gl::DrawArrays`].
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+struct Gles2Vertex {
+    // Which corner of the glyph quad this vertex is, in [0, 1] on each axis.
+    corner: [f32; 2],
+
+    // Coords.
+    coords: [u16; 2],
+
+    // Glyph offset and size.
+    glyph_offset_size: [i16; 4],
+
+    // UV offset and scale.
+    uv: [f32; 4],
 }
@@ -1,11 +1,41 @@
-use std::borrow::Cow;
 use std::ptr;
 
-use crossfont::RasterizedGlyph;
+use crossfont::{BitmapBuffer, RasterizedGlyph};
 
 use crate::gl;
 use crate::gl::types::*;
 
+/// How glyphs are rasterized and composited, as in WebRender's `FontRenderMode`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum FontRenderMode {
+    /// Threshold coverage to fully on/off, for a crisp bitmap-font look.
+    Mono,
+    /// A single antialiased coverage value per pixel, replicated across all channels.
+    Grayscale,
+    /// Independent coverage per subpixel channel, for sharper text on LCD panels.
+    SubpixelLcd,
+}
+
+/// Glyph rasterization upload sink. Factoring this out of the draw loop means the caller doesn't
+/// need to know whether glyphs land on a single atlas, a growing set of pages, or (in tests) no
+/// GPU-backed atlas at all.
+pub trait LoadGlyph {
+    /// Upload `rasterized` and return the atlas entry describing where it landed. `render_mode`
+    /// picks whether the uploaded mask carries independent per-channel coverage (see
+    /// [`Glyph::lcd`]).
+    fn load_glyph(&mut self, rasterized: &RasterizedGlyph, render_mode: FontRenderMode) -> Glyph;
+
+    /// Free the atlas region backing a previously loaded glyph, e.g. because it was evicted from
+    /// a bounding [`crate::renderer::glyph_cache::GlyphCache`]. The shelf-packed [`Atlas`] can't
+    /// reclaim individual regions without fragmenting, so this is a no-op by default; allocators
+    /// that can act on it (e.g. one backed by a free-list) should override it.
+    fn unload_glyph(&mut self, _glyph: &Glyph) {}
+
+    /// Discard everything loaded so far and reset to a single fresh page, e.g. after a font or
+    /// DPI change where the old raster sizes must not be reused.
+    fn clear(&mut self);
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct Glyph {
     pub tex_id: GLuint,
@@ -17,6 +47,14 @@ pub struct Glyph {
     pub uv_left: f32,
     pub uv_width: f32,
     pub uv_height: f32,
+
+    /// Whether this glyph's texture stores real RGBA color (e.g. a color emoji) rather than a
+    /// coverage mask that gets tinted with the cell's foreground color.
+    pub colored: bool,
+
+    /// Whether this glyph's texture carries 3 independent per-channel coverage values (subpixel
+    /// LCD) rather than a single coverage value replicated across all three.
+    pub lcd: bool,
 }
 
 /// Size of the Atlas.
@@ -48,6 +86,7 @@ pub struct Atlas {
     row_extent: i32,   // Left-most free pixel in a row.
     row_baseline: i32, // Baseline for glyphs in the current row.
     row_tallest: i32,  // Tallest glyph in current row.
+    glyph_count: u32,
 }
 
 impl Atlas {
@@ -79,18 +118,59 @@ impl Atlas {
             gl::BindTexture(gl::TEXTURE_2D, 0);
         }
 
-        Self { id, width: size, height: size, row_extent: 0, row_baseline: 0, row_tallest: 0 }
+        Self {
+            id,
+            width: size,
+            height: size,
+            row_extent: 0,
+            row_baseline: 0,
+            row_tallest: 0,
+            glyph_count: 0,
+        }
+    }
+
+    /// Number of glyphs packed into this atlas so far.
+    pub fn glyph_count(&self) -> u32 {
+        self.glyph_count
+    }
+
+    /// Rough fraction of the atlas's vertical space consumed by finished and in-progress rows.
+    /// `Atlas` is single-page and never evicts, so this is a reasonable stand-in for "how full".
+    pub fn occupancy(&self) -> f32 {
+        ((self.row_baseline + self.row_tallest) as f32 / self.height as f32).min(1.0)
+    }
+
+    /// Whether `glyph` still fits on the current row, or on a fresh row below it. `false` means
+    /// the atlas is out of room and the caller must start a new page.
+    pub fn fits(&self, glyph: &RasterizedGlyph) -> bool {
+        if self.row_extent + glyph.width <= self.width
+            && self.row_baseline + glyph.height <= self.height
+        {
+            return true;
+        }
+
+        let next_baseline = self.row_baseline + self.row_tallest;
+        glyph.width <= self.width && next_baseline + glyph.height <= self.height
     }
 
     pub fn insert_inner(&mut self, glyph: &RasterizedGlyph) -> Glyph {
+        if self.row_extent + glyph.width > self.width {
+            self.row_baseline += self.row_tallest;
+            self.row_extent = 0;
+            self.row_tallest = 0;
+        }
+
         let offset_y = self.row_baseline;
         let offset_x = self.row_extent;
 
+        let (format, buffer) = match &glyph.buffer {
+            BitmapBuffer::Rgb(buf) => (gl::RGB, buf),
+            BitmapBuffer::Rgba(buf) => (gl::RGBA, buf),
+        };
+
         unsafe {
             gl::BindTexture(gl::TEXTURE_2D, self.id);
 
-            let buffer = Cow::Borrowed(&glyph.buffer);
-
             gl::TexSubImage2D(
                 gl::TEXTURE_2D,
                 0,
@@ -98,7 +178,7 @@ impl Atlas {
                 offset_y,
                 glyph.width,
                 glyph.height,
-                gl::RGB,
+                format,
                 gl::UNSIGNED_BYTE,
                 buffer.as_ptr() as *const _,
             );
@@ -111,6 +191,7 @@ impl Atlas {
         if glyph.height > self.row_tallest {
             self.row_tallest = glyph.height;
         }
+        self.glyph_count += 1;
 
         // Generate UV coordinates.
         let uv_bot = offset_y as f32 / self.height as f32;
@@ -143,6 +224,129 @@ impl Atlas {
             uv_left,
             uv_width,
             uv_height,
+            colored: matches!(glyph.buffer, BitmapBuffer::Rgba(_)),
+            // `Atlas` packs pages without knowing the render mode a glyph was rasterized for;
+            // `AtlasSet::load_glyph` fills this in afterward.
+            lcd: false,
         }
     }
 }
+
+/// One texture per atlas page; a new page is pushed once a glyph no longer fits on the last one,
+/// so an arbitrarily large number of glyphs can be cached without collisions.
+#[derive(Debug)]
+pub struct AtlasSet {
+    pages: Vec<Atlas>,
+}
+
+impl AtlasSet {
+    pub fn new() -> Self {
+        Self { pages: vec![Atlas::new(ATLAS_SIZE)] }
+    }
+
+    /// Fraction of the active page's vertical space consumed so far; for the debug overlay.
+    pub fn occupancy(&self) -> f32 {
+        self.pages.last().map(Atlas::occupancy).unwrap_or_default()
+    }
+
+    /// Number of glyphs packed into every page so far; for the debug overlay.
+    pub fn glyph_count(&self) -> u32 {
+        self.pages.iter().map(Atlas::glyph_count).sum()
+    }
+}
+
+impl Default for AtlasSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoadGlyph for AtlasSet {
+    fn load_glyph(&mut self, rasterized: &RasterizedGlyph, render_mode: FontRenderMode) -> Glyph {
+        let fits_last = self.pages.last().is_some_and(|atlas| atlas.fits(rasterized));
+        if !fits_last {
+            self.pages.push(Atlas::new(ATLAS_SIZE));
+        }
+
+        let mut glyph = self.pages.last_mut().unwrap().insert_inner(rasterized);
+        glyph.lcd = render_mode == FontRenderMode::SubpixelLcd;
+        glyph
+    }
+
+    fn clear(&mut self) {
+        self.pages.clear();
+        self.pages.push(Atlas::new(ATLAS_SIZE));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build an `Atlas` with no backing GL texture, since `fits`'s shelf-packing math never
+    /// touches `id` -- only `Atlas::new`/`insert_inner` do, and those require a real GL context.
+    fn test_atlas(size: i32) -> Atlas {
+        Atlas {
+            id: 0,
+            width: size,
+            height: size,
+            row_extent: 0,
+            row_baseline: 0,
+            row_tallest: 0,
+            glyph_count: 0,
+        }
+    }
+
+    fn rasterized_glyph(width: i32, height: i32) -> RasterizedGlyph {
+        RasterizedGlyph {
+            character: 'x',
+            width,
+            height,
+            top: 0,
+            left: 0,
+            advance: (0, 0),
+            buffer: BitmapBuffer::Rgb(vec![0; (width * height * 3).max(0) as usize]),
+        }
+    }
+
+    #[test]
+    fn fits_on_empty_row() {
+        let atlas = test_atlas(64);
+        assert!(atlas.fits(&rasterized_glyph(32, 16)));
+    }
+
+    #[test]
+    fn does_not_fit_wider_than_atlas() {
+        let atlas = test_atlas(64);
+        assert!(!atlas.fits(&rasterized_glyph(128, 16)));
+    }
+
+    #[test]
+    fn does_not_fit_taller_than_atlas() {
+        let atlas = test_atlas(64);
+        assert!(!atlas.fits(&rasterized_glyph(16, 128)));
+    }
+
+    #[test]
+    fn fits_on_next_row_when_current_row_is_full() {
+        let mut atlas = test_atlas(64);
+        atlas.row_extent = 64;
+        atlas.row_baseline = 0;
+        atlas.row_tallest = 16;
+
+        // Doesn't fit on the current (full) row, but the next row starting at baseline + tallest
+        // has enough height left.
+        assert!(atlas.fits(&rasterized_glyph(32, 16)));
+    }
+
+    #[test]
+    fn does_not_fit_when_next_row_overflows_height() {
+        let mut atlas = test_atlas(64);
+        atlas.row_extent = 64;
+        atlas.row_baseline = 48;
+        atlas.row_tallest = 16;
+
+        // Next row would start at baseline 64, leaving no room for any height at all.
+        assert!(!atlas.fits(&rasterized_glyph(32, 16)));
+    }
+}
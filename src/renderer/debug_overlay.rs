@@ -0,0 +1,276 @@
+//! Built-in, always-available debug/performance overlay.
+//!
+//! Draws a translucent panel through the existing [`super::rects::RectRenderer`], with the text
+//! itself rendered through its own minimal instanced-quad shader and vertex buffer instead of the
+//! main glyph atlas, so the overlay keeps working even when the text pipeline it's reporting on
+//! is misbehaving. The overlay's "font" is a built-in 3x5 pixel bitmap covering just the
+//! characters its own stats lines use -- no external asset, no dependency on `GlyphCache`.
+
+use std::ffi::CString;
+use std::mem::size_of;
+use std::ptr;
+
+use crate::display::SizeInfo;
+use crate::gl;
+use crate::gl::types::*;
+use crate::renderer::rects::{RectRenderer, RenderRect};
+
+static OVERLAY_SHADER_V: &str = include_str!("../../res/debug_overlay.v.glsl");
+static OVERLAY_SHADER_F: &str = include_str!("../../res/debug_overlay.f.glsl");
+
+/// Rolling per-frame timings and atlas occupancy, gathered by the render thread around its
+/// `draw_cells` call.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameStats {
+    /// Time spent inside the `draw_cells` call, covering glyph rasterization and atlas uploads.
+    pub rasterize_ms: f32,
+    /// Total time the render thread spent on the previous frame, including its buffer swap.
+    pub frame_ms: f32,
+    /// Number of `glDrawArrays`/`glDrawElements` calls issued this frame.
+    pub draw_calls: u32,
+    /// Number of glyphs packed into every atlas page so far.
+    pub atlas_glyph_count: u32,
+    /// Fraction (0.0-1.0) of the active atlas's vertical space consumed so far.
+    pub atlas_occupancy: f32,
+}
+
+impl FrameStats {
+    pub fn fps(&self) -> f32 {
+        if self.frame_ms > 0.0 {
+            1000.0 / self.frame_ms
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Screen-pixel size of one bitmap-font pixel, before it's expanded into a quad.
+const PIXEL_SIZE: f32 = 3.0;
+const GLYPH_COLS: i32 = 3;
+const GLYPH_ROWS: i32 = 5;
+const GLYPH_ADVANCE: f32 = (GLYPH_COLS + 1) as f32 * PIXEL_SIZE;
+const LINE_ADVANCE: f32 = (GLYPH_ROWS + 2) as f32 * PIXEL_SIZE;
+
+/// One filled pixel of a glyph, positioned in screen space; fed to the shader as a per-instance
+/// vertex attribute.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct PixelInstance {
+    x: f32,
+    y: f32,
+}
+
+/// Built-in debug/performance overlay, drawn on top of each frame when [`Self::enabled`].
+#[derive(Debug)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+    program: GLuint,
+    u_resolution: GLint,
+    u_pixel_size: GLint,
+    vao: GLuint,
+    vbo: GLuint,
+    pixels: Vec<PixelInstance>,
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        let mut vao: GLuint = 0;
+        let mut vbo: GLuint = 0;
+
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+
+            gl::VertexAttribPointer(
+                0,
+                2,
+                gl::FLOAT,
+                gl::FALSE,
+                size_of::<PixelInstance>() as i32,
+                ptr::null(),
+            );
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribDivisor(0, 1);
+
+            gl::BindVertexArray(0);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+
+        macro_rules! cstr {
+            ($s:literal) => {
+                std::ffi::CStr::from_ptr(concat!($s, "\0").as_ptr().cast())
+            };
+        }
+
+        let program = unsafe {
+            let vertex_shader = compile(gl::VERTEX_SHADER, OVERLAY_SHADER_V);
+            let fragment_shader = compile(gl::FRAGMENT_SHADER, OVERLAY_SHADER_F);
+
+            let program = gl::CreateProgram();
+            gl::AttachShader(program, vertex_shader);
+            gl::AttachShader(program, fragment_shader);
+            gl::LinkProgram(program);
+            gl::DeleteShader(vertex_shader);
+            gl::DeleteShader(fragment_shader);
+            program
+        };
+
+        let (u_resolution, u_pixel_size) = unsafe {
+            (
+                gl::GetUniformLocation(program, cstr!("resolution").as_ptr()),
+                gl::GetUniformLocation(program, cstr!("pixelSize").as_ptr()),
+            )
+        };
+
+        Self { enabled: false, program, u_resolution, u_pixel_size, vao, vbo, pixels: Vec::new() }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    /// The translucent background panel for this frame's overlay, drawn through the caller's
+    /// `RectRenderer`; `None` when the overlay is disabled.
+    pub fn panel_rect(&self, size_info: &SizeInfo) -> Option<RenderRect> {
+        if !self.enabled {
+            return None;
+        }
+
+        let width = 13.0 * GLYPH_ADVANCE;
+        let height = 5.0 * LINE_ADVANCE;
+        Some(RenderRect::new(
+            size_info.padding_x,
+            size_info.padding_y,
+            width,
+            height,
+            crate::display::Rgb::new(0, 0, 0),
+            0.6,
+        ))
+    }
+
+    /// Draw the background panel (through `rect_renderer`) and the stats text (through the
+    /// overlay's own pipeline) for this frame. No-op when disabled.
+    pub fn draw(&mut self, size_info: &SizeInfo, rect_renderer: &mut RectRenderer, stats: &FrameStats) {
+        if !self.enabled {
+            return;
+        }
+
+        if let Some(panel) = self.panel_rect(size_info) {
+            rect_renderer.draw(size_info, vec![panel]);
+        }
+
+        let lines = [
+            format!("FPS {:.1}", stats.fps()),
+            format!("FRAME {:.2}", stats.frame_ms),
+            format!("RASTER {:.2}", stats.rasterize_ms),
+            format!("ATLAS {}/{:.0}", stats.atlas_glyph_count, stats.atlas_occupancy * 100.0),
+            format!("DRAWS {}", stats.draw_calls),
+        ];
+
+        self.pixels.clear();
+        let origin_x = size_info.padding_x + PIXEL_SIZE;
+        let mut y = size_info.padding_y + PIXEL_SIZE;
+        for line in &lines {
+            let mut x = origin_x;
+            for c in line.chars() {
+                let bits = glyph_bits(c.to_ascii_uppercase());
+                for (row, bits_row) in bits.iter().enumerate() {
+                    for col in 0..GLYPH_COLS {
+                        if bits_row & (1 << (GLYPH_COLS - 1 - col)) != 0 {
+                            self.pixels.push(PixelInstance {
+                                x: x + col as f32 * PIXEL_SIZE,
+                                y: y + row as f32 * PIXEL_SIZE,
+                            });
+                        }
+                    }
+                }
+                x += GLYPH_ADVANCE;
+            }
+            y += LINE_ADVANCE;
+        }
+
+        if self.pixels.is_empty() {
+            return;
+        }
+
+        unsafe {
+            gl::UseProgram(self.program);
+            gl::Uniform2f(self.u_resolution, size_info.width, size_info.height);
+            gl::Uniform1f(self.u_pixel_size, PIXEL_SIZE);
+
+            gl::BindVertexArray(self.vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (self.pixels.len() * size_of::<PixelInstance>()) as isize,
+                self.pixels.as_ptr() as *const _,
+                gl::STREAM_DRAW,
+            );
+
+            gl::DrawArraysInstanced(gl::TRIANGLE_STRIP, 0, 4, self.pixels.len() as GLsizei);
+
+            gl::BindVertexArray(0);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::UseProgram(0);
+        }
+    }
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for DebugOverlay {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgram(self.program);
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
+
+unsafe fn compile(kind: GLenum, source: &str) -> GLuint {
+    let shader = gl::CreateShader(kind);
+    let source = CString::new(source).unwrap();
+    gl::ShaderSource(shader, 1, &source.as_ptr(), ptr::null());
+    gl::CompileShader(shader);
+    shader
+}
+
+/// Lookup into the overlay's built-in 3x5 bitmap font. Only covers the characters the overlay's
+/// own stats lines use; anything else renders blank.
+fn glyph_bits(c: char) -> [u8; 5] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' | 'S' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        'F' => [0b111, 0b100, 0b111, 0b100, 0b100],
+        'P' => [0b111, 0b101, 0b111, 0b100, 0b100],
+        'R' => [0b111, 0b101, 0b111, 0b110, 0b101],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'E' => [0b111, 0b100, 0b111, 0b100, 0b111],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'W' => [0b101, 0b101, 0b101, 0b111, 0b101],
+        _ => [0; 5],
+    }
+}
@@ -1,33 +1,278 @@
-use crossfont::Size;
-use crossfont::{FontDesc, FontKey, Metrics, Rasterizer};
+use std::collections::{HashMap, VecDeque};
 
-use crate::gl::types::*;
+use crossfont::{
+    BitmapBuffer, FontDesc, FontKey, GlyphKey, Metrics, RasterizedGlyph, Rasterizer, Size,
+};
 
-#[derive(Copy, Clone, Debug)]
-pub struct Glyph {
-    pub tex_id: GLuint,
-    pub top: i16,
-    pub left: i16,
-    pub width: i16,
-    pub height: i16,
-    pub uv_bot: f32,
-    pub uv_left: f32,
-    pub uv_width: f32,
-    pub uv_height: f32,
+use crate::renderer::atlas::{FontRenderMode, Glyph, LoadGlyph};
+
+/// Default gamma used by [`GammaLut`], in WebRender's usual 1.8-2.2 range.
+const DEFAULT_GAMMA: f64 = 1.8;
+
+/// Default contrast factor used by [`GammaLut`].
+const DEFAULT_CONTRAST: f64 = 0.1;
+
+/// Default number of glyphs kept resident before the cache starts evicting.
+const DEFAULT_CACHE_CAPACITY: usize = 2048;
+
+/// Number of horizontal subpixel phases a glyph is cached at, as in Pathfinder/WebRender.
+///
+/// The fractional part of the pen's x position is quantized into one of this many buckets, and
+/// each bucket gets its own cache entry. The phase is not passed to the rasterizer, so every bucket
+/// holds the same integer-aligned rasterization. Bucket `0` always covers the integer-aligned
+/// phase, so monospace rendering (which always lands on bucket `0`) is unchanged by this cache key
+/// extension.
+const SUBPIXEL_VARIANTS: u8 = 4;
+
+/// Quantized horizontal subpixel phase of a glyph's pen position.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+struct SubpixelOffset(u8);
+
+impl SubpixelOffset {
+    /// Quantize the fractional part of a pen's x position into a cacheable phase.
+    fn quantize(pen_x: f32) -> Self {
+        let frac = pen_x.fract().abs();
+        SubpixelOffset(((frac * f32::from(SUBPIXEL_VARIANTS)).round() as u8) % SUBPIXEL_VARIANTS)
+    }
+}
+
+/// An OpenType font-variation axis setting, as WebRender's `FontVariation` list.
+///
+/// E.g. `FontVariation { tag: *b"wght", value: 350.0 }` names a weight of 350 on a variable
+/// font's `wght` axis.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FontVariation {
+    pub tag: [u8; 4],
+    pub value: f32,
+}
+
+/// Key used by the glyph cache, extending `GlyphKey` with the subpixel phase it was rasterized
+/// for and the variation-axis generation active at the time.
+///
+/// Axis values are `f32` and thus not `Hash`/`Eq`; rather than hash the bit pattern, the active
+/// variation set is identified by a generation counter bumped on every change in
+/// `GlyphCache::set_variations`, so glyphs rasterized under different axis settings never
+/// collide in the cache.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+struct CacheKey {
+    glyph_key: GlyphKey,
+    subpixel: SubpixelOffset,
+    variation_generation: u64,
+    render_mode: FontRenderMode,
+}
+
+/// Gamma-correction lookup table for glyph coverage, as used by WebRender's `gamma_lut`.
+///
+/// Compositing coverage directly (`src * alpha + dst * (1 - alpha)`) makes light-on-dark text
+/// look thinner than dark-on-light text at the same nominal weight, because blending happens in
+/// gamma space instead of linear space. The table remaps each `(reference_luminance, coverage)`
+/// pair through a gamma curve plus a luminance-dependent contrast term so both polarities read
+/// with the same apparent weight.
+#[derive(Debug)]
+struct GammaLut {
+    gamma: f64,
+    contrast: f64,
+    table: Box<[[u8; 256]; 256]>,
+}
+
+impl GammaLut {
+    /// Build a new table for the given `gamma` (typically 1.8-2.2) and `contrast` factor.
+    ///
+    /// `gamma == 1.0` and `contrast == 0.0` is the identity transform: `table[l][c] == c` for
+    /// every `l`/`c`, so callers that don't want gamma correction see no change in output.
+    fn new(gamma: f64, contrast: f64) -> Self {
+        let mut table = Box::new([[0u8; 256]; 256]);
+
+        for (reference_luminance, row) in table.iter_mut().enumerate() {
+            let luminance = reference_luminance as f64 / 255.0;
+            // Darken coverage more aggressively as the reference luminance gets brighter, like
+            // WebRender's `gamma_lut` contrast term.
+            let contrast_factor = 1.0 + contrast * luminance;
+
+            for (coverage, entry) in row.iter_mut().enumerate() {
+                let linear = (coverage as f64 / 255.0).powf(gamma);
+                let adjusted = (linear * contrast_factor).clamp(0.0, 1.0);
+                *entry = (adjusted.powf(1.0 / gamma) * 255.0).round() as u8;
+            }
+        }
+
+        Self { gamma, contrast, table }
+    }
+
+    /// `true` when this table is a pass-through (gamma correction disabled).
+    fn is_identity(&self) -> bool {
+        self.gamma == 1.0 && self.contrast == 0.0
+    }
+
+    /// Remap the coverage samples of `glyph` through `table[luminance]` in place.
+    fn correct(&self, luminance: u8, glyph: &mut RasterizedGlyph) {
+        if self.is_identity() {
+            return;
+        }
+
+        let row = &self.table[luminance as usize];
+        match &mut glyph.buffer {
+            BitmapBuffer::Rgb(buf) | BitmapBuffer::Rgba(buf) => {
+                for sample in buf.iter_mut() {
+                    *sample = row[*sample as usize];
+                }
+            },
+        }
+    }
 }
 
+/// Rec. 601 luma of an `[r, g, b]` foreground color, used to pick the gamma-correction row.
+fn luminance(rgb: [u8; 3]) -> u8 {
+    (0.299 * rgb[0] as f64 + 0.587 * rgb[1] as f64 + 0.114 * rgb[2] as f64).round() as u8
+}
+
+/// Collapse coverage to fully on/off for [`FontRenderMode::Mono`], thresholding at the midpoint
+/// rather than antialiasing, for a crisp bitmap-font look.
+fn threshold(glyph: &mut RasterizedGlyph) {
+    match &mut glyph.buffer {
+        BitmapBuffer::Rgb(buf) | BitmapBuffer::Rgba(buf) => {
+            for sample in buf.iter_mut() {
+                *sample = if *sample >= 128 { 255 } else { 0 };
+            }
+        },
+    }
+}
+
+/// A style that must be synthesized from the regular face because no matching real face was
+/// found (e.g. the font only ships a Regular weight).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum SyntheticStyle {
+    Bold,
+    Italic,
+    BoldItalic,
+}
+
+/// Shear angle used for synthetic italics, matching the ~14 degree slant common fonts use.
+const SYNTHETIC_ITALIC_SHEAR: f32 = 0.25;
+
+/// Apply a synthetic style transform to a glyph rasterized from the regular face.
+fn synthesize(style: SyntheticStyle, glyph: &mut RasterizedGlyph) {
+    match style {
+        SyntheticStyle::Bold => embolden(glyph),
+        SyntheticStyle::Italic => shear(glyph),
+        SyntheticStyle::BoldItalic => {
+            embolden(glyph);
+            shear(glyph);
+        },
+    }
+}
+
+/// Embolden a glyph by dilating its coverage one pixel to the right (each sample takes the max of
+/// itself and its left neighbor), bumping
+/// the advance to match the extra ink.
+fn embolden(glyph: &mut RasterizedGlyph) {
+    let (width, height) = (glyph.width as usize, glyph.height as usize);
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let channels = match &glyph.buffer {
+        BitmapBuffer::Rgb(_) => 3,
+        BitmapBuffer::Rgba(_) => 4,
+    };
+
+    let dilate = |buf: &mut Vec<u8>| {
+        let original = buf.clone();
+        for y in 0..height {
+            for x in 0..width {
+                for c in 0..channels {
+                    let i = (y * width + x) * channels + c;
+                    let left = x.checked_sub(1).map(|x| (y * width + x) * channels + c);
+                    let neighbor = left.map_or(0, |i| original[i]);
+                    buf[i] = buf[i].max(neighbor);
+                }
+            }
+        }
+    };
+
+    match &mut glyph.buffer {
+        BitmapBuffer::Rgb(buf) | BitmapBuffer::Rgba(buf) => dilate(buf),
+    }
+
+    glyph.advance.0 += 1;
+}
+
+/// Shear a glyph horizontally to synthesize italics, shifting each row right by
+/// `row_from_baseline * SYNTHETIC_ITALIC_SHEAR` and widening the bitmap so nothing clips.
+fn shear(glyph: &mut RasterizedGlyph) {
+    let (width, height) = (glyph.width as usize, glyph.height as usize);
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let channels = match &glyph.buffer {
+        BitmapBuffer::Rgb(_) => 3,
+        BitmapBuffer::Rgba(_) => 4,
+    };
+
+    let max_shift = (height as f32 * SYNTHETIC_ITALIC_SHEAR).ceil() as usize;
+    let sheared_width = width + max_shift;
+
+    let shear_buffer = |buf: &[u8]| -> Vec<u8> {
+        let mut out = vec![0u8; sheared_width * height * channels];
+        for y in 0..height {
+            // Row `0` is the topmost row; the shift is largest there and falls to zero at the
+            // bottom row.
+            let row_from_baseline = height - 1 - y;
+            let shift = (row_from_baseline as f32 * SYNTHETIC_ITALIC_SHEAR).round() as usize;
+
+            for x in 0..width {
+                for c in 0..channels {
+                    let src = (y * width + x) * channels + c;
+                    let dst = (y * sheared_width + x + shift) * channels + c;
+                    out[dst] = buf[src];
+                }
+            }
+        }
+        out
+    };
+
+    glyph.buffer = match &glyph.buffer {
+        BitmapBuffer::Rgb(buf) => BitmapBuffer::Rgb(shear_buffer(buf)),
+        BitmapBuffer::Rgba(buf) => BitmapBuffer::Rgba(shear_buffer(buf)),
+    };
+
+    glyph.width = sheared_width as i32;
+    glyph.left -= max_shift as i32;
+}
+
+/// Caches rasterized glyphs behind a [`LoadGlyph`] sink, and owns the regular/bold/italic/bold
+/// italic font faces text rendering rasterizes from.
+#[derive(Debug)]
 pub struct GlyphCache {
-    pub rasterizer: Rasterizer,
+    cache: HashMap<CacheKey, Glyph>,
+    /// Cache keys ordered from least- to most-recently used; the back is the most recent.
+    lru: VecDeque<CacheKey>,
+    /// Maximum number of resident glyphs before the oldest entries are evicted.
+    capacity: usize,
+    gamma_lut: GammaLut,
+    /// Maps a synthesized `FontKey` (as stored in `bold_key`/`italic_key`/`bold_italic_key`) to
+    /// the style to synthesize; such keys are always rasterized from the regular face
+    /// (`font_key`).
+    synthetic: HashMap<FontKey, SyntheticStyle>,
+    /// Currently active variation-axis settings. They only key the cache (through
+    /// `variation_generation`); they are not passed to the `Rasterizer`, so glyphs are always
+    /// rasterized from the static faces loaded in `new`.
+    variations: Vec<FontVariation>,
+    /// Bumped every time `variations` changes, so cached glyphs from a previous axis setting
+    /// are never returned for the current one.
+    variation_generation: u64,
+    render_mode: FontRenderMode,
     pub font_key: FontKey,
     pub bold_key: FontKey,
     pub italic_key: FontKey,
     pub bold_italic_key: FontKey,
-    pub font_size: crossfont::Size,
+    pub font_size: Size,
     pub metrics: Metrics,
 }
 
 impl GlyphCache {
-    pub fn new(mut rasterizer: Rasterizer) -> GlyphCache {
+    pub fn new(rasterizer: &mut Rasterizer) -> Result<GlyphCache, super::Error> {
         let font_name = String::from("Source Code Pro");
         let font_size = Size::new(16.);
 
@@ -36,13 +281,228 @@ impl GlyphCache {
         let italic_desc = FontDesc::new(&font_name, &String::from("Italic"));
         let bold_italic_desc = FontDesc::new(&font_name, &String::from("Bold Italic"));
 
-        let font_key = rasterizer.load_font(&regular_desc, font_size).unwrap();
-        let bold_key = rasterizer.load_font(&bold_desc, font_size).unwrap();
-        let italic_key = rasterizer.load_font(&italic_desc, font_size).unwrap();
-        let bold_italic_key = rasterizer.load_font(&bold_italic_desc, font_size).unwrap();
+        // Unlike bold/italic/bold-italic, there's no face to synthesize the regular style over
+        // if it's missing, so a failure here is fatal to the cache.
+        let font_key = rasterizer.load_font(&regular_desc, font_size)?;
+
+        let mut synthetic = HashMap::new();
+        let bold_key = Self::load_or_synthesize(
+            rasterizer,
+            &bold_desc,
+            font_size,
+            SyntheticStyle::Bold,
+            &mut synthetic,
+        );
+        let italic_key = Self::load_or_synthesize(
+            rasterizer,
+            &italic_desc,
+            font_size,
+            SyntheticStyle::Italic,
+            &mut synthetic,
+        );
+        let bold_italic_key = Self::load_or_synthesize(
+            rasterizer,
+            &bold_italic_desc,
+            font_size,
+            SyntheticStyle::BoldItalic,
+            &mut synthetic,
+        );
+
+        let metrics = rasterizer.metrics(font_key, font_size)?;
+
+        Ok(Self {
+            cache: HashMap::new(),
+            lru: VecDeque::new(),
+            capacity: DEFAULT_CACHE_CAPACITY,
+            gamma_lut: GammaLut::new(DEFAULT_GAMMA, DEFAULT_CONTRAST),
+            synthetic,
+            variations: Vec::new(),
+            variation_generation: 0,
+            render_mode: FontRenderMode::Grayscale,
+            font_size,
+            font_key,
+            bold_key,
+            italic_key,
+            bold_italic_key,
+            metrics,
+        })
+    }
+
+    /// Load `desc`, falling back to synthesizing `style` over the regular face if it's missing.
+    ///
+    /// The fallback key minted here is a fresh, distinct `FontKey` that isn't known to the
+    /// `Rasterizer` directly -- `get` resolves it back to the regular face through `synthetic`
+    /// before rasterizing, and applies the style transform afterwards.
+    fn load_or_synthesize(
+        rasterizer: &mut Rasterizer,
+        desc: &FontDesc,
+        font_size: Size,
+        style: SyntheticStyle,
+        synthetic: &mut HashMap<FontKey, SyntheticStyle>,
+    ) -> FontKey {
+        match rasterizer.load_font(desc, font_size) {
+            Ok(key) => key,
+            Err(_) => {
+                let key = FontKey::next();
+                synthetic.insert(key, style);
+                key
+            },
+        }
+    }
+
+    /// Configure the gamma-correction table used when uploading glyph coverage.
+    ///
+    /// `gamma` of `1.0` with `contrast` of `0.0` disables gamma correction entirely.
+    pub fn set_gamma(&mut self, gamma: f64, contrast: f64) {
+        self.gamma_lut = GammaLut::new(gamma, contrast);
+    }
+
+    /// Set the maximum number of resident glyphs, evicting the least-recently-used entries if
+    /// the cache is currently over the new limit.
+    pub fn set_capacity<L: LoadGlyph + ?Sized>(&mut self, capacity: usize, loader: &mut L) {
+        self.capacity = capacity;
+        self.evict_excess(loader);
+    }
+
+    /// Replace the active variation-axis settings.
+    ///
+    /// Bumps the variation generation so every subsequent `get` misses the cache and
+    /// re-rasterizes (from the same static faces; see `variations`); returns `true` if the
+    /// settings actually changed, which
+    /// callers should use to mark their window dirty and force a redraw.
+    pub fn set_variations(&mut self, variations: Vec<FontVariation>) -> bool {
+        if variations == self.variations {
+            return false;
+        }
 
-        let metrics = rasterizer.metrics(font_key, font_size);
+        self.variations = variations;
+        self.variation_generation += 1;
+        true
+    }
+
+    /// Smoothly interpolate a single axis toward `target`, moving at most `step` per call.
+    ///
+    /// Useful for animating e.g. a `wght` axis. Returns `true` while the axis is still short of
+    /// `target`, so callers can keep driving the animation and stop once it settles.
+    pub fn step_axis_toward(&mut self, tag: [u8; 4], target: f32, step: f32) -> bool {
+        let axis = self.variations.iter_mut().find(|variation| variation.tag == tag);
+        let axis = match axis {
+            Some(axis) => axis,
+            None => {
+                self.variations.push(FontVariation { tag, value: target });
+                self.variation_generation += 1;
+                return false;
+            },
+        };
+
+        let delta = target - axis.value;
+        if delta.abs() <= step {
+            if delta != 0.0 {
+                axis.value = target;
+                self.variation_generation += 1;
+            }
+            return false;
+        }
+
+        axis.value += step * delta.signum();
+        self.variation_generation += 1;
+        true
+    }
+
+    /// Select how glyphs are rasterized and composited. Pick a sensible platform default (e.g.
+    /// `SubpixelLcd` on a non-HiDPI LCD panel) and fall back to `Grayscale` whenever the LCD
+    /// subpixel layout isn't known, since blending with the wrong channel order looks worse than
+    /// plain antialiasing.
+    pub fn set_render_mode(&mut self, render_mode: FontRenderMode) {
+        self.render_mode = render_mode;
+    }
+
+    /// Mark `cache_key` as the most-recently used entry.
+    fn touch(&mut self, cache_key: CacheKey) {
+        if let Some(pos) = self.lru.iter().position(|key| *key == cache_key) {
+            self.lru.remove(pos);
+        }
+        self.lru.push_back(cache_key);
+    }
+
+    /// Evict least-recently-used glyphs until the cache is back within `capacity`.
+    fn evict_excess<L: LoadGlyph + ?Sized>(&mut self, loader: &mut L) {
+        while self.cache.len() > self.capacity {
+            let Some(oldest) = self.lru.pop_front() else { break };
+            if let Some(glyph) = self.cache.remove(&oldest) {
+                loader.unload_glyph(&glyph);
+            }
+        }
+    }
+
+    /// Get the glyph for `glyph_key`, cached under the subpixel phase of `pen_x` and
+    /// gamma-corrected against `fg`'s luminance.
+    ///
+    /// `pen_x` is the pixel-space x position the glyph will be drawn at; its fractional part
+    /// picks which of the [`SUBPIXEL_VARIANTS`] cache entries is used.
+    pub fn get<L: LoadGlyph + ?Sized>(
+        &mut self,
+        glyph_key: GlyphKey,
+        pen_x: f32,
+        fg: [u8; 3],
+        rasterizer: &mut Rasterizer,
+        loader: &mut L,
+    ) -> Glyph {
+        let cache_key = CacheKey {
+            glyph_key,
+            subpixel: SubpixelOffset::quantize(pen_x),
+            variation_generation: self.variation_generation,
+            render_mode: self.render_mode,
+        };
+
+        if let Some(glyph) = self.cache.get(&cache_key).copied() {
+            self.touch(cache_key);
+            return glyph;
+        }
+
+        // A synthesized style rasterizes from the regular face and applies a transform on top,
+        // rather than rasterizing `glyph_key.font_key` (which the `Rasterizer` doesn't know).
+        let synthetic_style = self.synthetic.get(&glyph_key.font_key).copied();
+        let raster_key = match synthetic_style {
+            Some(_) => GlyphKey { font_key: self.font_key, ..glyph_key },
+            None => glyph_key,
+        };
+
+        // `MissingGlyph` is the expected result for any character the loaded face doesn't cover
+        // (CJK, most emoji, box-drawing, ...), not a rarely-hit failure, so it must not panic;
+        // crossfont already hands back a placeholder glyph to rasterize in its place.
+        rasterizer.set_render_mode(match self.render_mode {
+            FontRenderMode::Mono => crossfont::FontRenderMode::Mono,
+            FontRenderMode::Grayscale => crossfont::FontRenderMode::GrayscaleAa,
+            FontRenderMode::SubpixelLcd => crossfont::FontRenderMode::SubpixelAa,
+        });
+        let mut rasterized: RasterizedGlyph = match rasterizer.get_glyph(raster_key) {
+            Ok(rasterized) => rasterized,
+            Err(crossfont::Error::MissingGlyph(placeholder)) => placeholder,
+            Err(err) => panic!("failed to rasterize glyph: {err}"),
+        };
+        if let Some(style) = synthetic_style {
+            synthesize(style, &mut rasterized);
+        }
+
+        self.gamma_lut.correct(luminance(fg), &mut rasterized);
+
+        if self.render_mode == FontRenderMode::Mono {
+            threshold(&mut rasterized);
+        }
+
+        let glyph = loader.load_glyph(&rasterized, self.render_mode);
+        self.cache.insert(cache_key, glyph);
+        self.touch(cache_key);
+        self.evict_excess(loader);
+
+        glyph
+    }
 
-        Self { rasterizer, font_size, font_key, bold_key, italic_key, bold_italic_key, metrics }
+    /// Forget every cached glyph, e.g. after a DPI change where the caller is about to clear the
+    /// atlas these entries' `tex_id`s point into.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.lru.clear();
     }
 }
@@ -0,0 +1,87 @@
+//! Optional live shader reloading, enabled with the `live-shader-reload` feature.
+//!
+//! The `include_str!`-embedded sources remain the compiled-in default; when the feature is on,
+//! renderers additionally watch the on-disk `res/*.glsl` files and offer a recompiled program the
+//! next time [`ShaderWatcher::poll_changed`] is asked, so editing a shader updates the running
+//! window without a restart.
+
+use std::path::PathBuf;
+
+#[cfg(feature = "live-shader-reload")]
+use std::sync::mpsc::{self, Receiver};
+#[cfg(feature = "live-shader-reload")]
+use std::time::Duration;
+
+#[cfg(feature = "live-shader-reload")]
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Watches a set of shader source files and reports whether any of them changed since the last
+/// poll. Debounces bursts of filesystem events (editors often save in several writes) into a
+/// single pending reload.
+pub struct ShaderWatcher {
+    #[cfg(feature = "live-shader-reload")]
+    _watcher: RecommendedWatcher,
+    #[cfg(feature = "live-shader-reload")]
+    changes: Receiver<()>,
+}
+
+impl ShaderWatcher {
+    /// Start watching `paths`, or return `None` if live reload is disabled or the watcher
+    /// couldn't be created (e.g. the source tree isn't available at runtime).
+    #[cfg(feature = "live-shader-reload")]
+    pub fn new(paths: &[PathBuf]) -> Option<Self> {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if event.is_ok() {
+                // A send only fails once the receiving `ShaderWatcher` is gone; nothing to do.
+                let _ = tx.send(());
+            }
+        })
+        .ok()?;
+
+        for path in paths {
+            watcher.watch(path, RecursiveMode::NonRecursive).ok()?;
+        }
+
+        Some(Self { _watcher: watcher, changes: rx })
+    }
+
+    #[cfg(not(feature = "live-shader-reload"))]
+    pub fn new(_paths: &[PathBuf]) -> Option<Self> {
+        None
+    }
+
+    /// Debounce window: collapse a burst of save events into one reload.
+    #[cfg(feature = "live-shader-reload")]
+    const DEBOUNCE: Duration = Duration::from_millis(50);
+
+    /// `true` if a watched file changed since the last call.
+    pub fn poll_changed(&self) -> bool {
+        #[cfg(feature = "live-shader-reload")]
+        {
+            let Ok(()) = self.changes.recv_timeout(Duration::ZERO) else { return false };
+            // Drain any further events from the same save within the debounce window.
+            std::thread::sleep(Self::DEBOUNCE);
+            while self.changes.try_recv().is_ok() {}
+            true
+        }
+
+        #[cfg(not(feature = "live-shader-reload"))]
+        false
+    }
+}
+
+/// Read a shader source fresh from disk for recompilation. The result is leaked to obtain a
+/// `&'static str`, matching the lifetime `ShaderProgram::new`/`with_version` expect for the
+/// compiled-in sources -- acceptable since reloads only happen a handful of times per debug
+/// session, not per frame.
+pub fn read_source(path: &PathBuf) -> Option<&'static str> {
+    let source = std::fs::read_to_string(path).ok()?;
+    Some(Box::leak(source.into_boxed_str()))
+}
+
+/// The on-disk path for a `res/`-relative shader, resolved against the crate root so live
+/// reload keeps working regardless of the binary's current working directory.
+pub fn res_path(relative: &str) -> PathBuf {
+    PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/res")).join(relative)
+}
@@ -0,0 +1,113 @@
+use std::num::NonZeroU32;
+
+use glutin::config::{Config, ConfigTemplateBuilder, GetGlConfig};
+use glutin::context::{ContextApi, ContextAttributesBuilder, NotCurrentContext, Version};
+use glutin::display::{Display, DisplayApiPreference, GetGlDisplay};
+use glutin::prelude::*;
+use glutin::surface::{Surface, SurfaceAttributesBuilder, WindowSurface};
+
+use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
+use winit::dpi::PhysicalSize;
+
+/// Create the GL display backing every window this process creates, picking the windowing API
+/// native to the current platform so callers (e.g. `WindowContext::initial`) don't need their own
+/// `#[cfg]`s.
+pub fn create_gl_display(
+    raw_display_handle: RawDisplayHandle,
+    raw_window_handle: Option<RawWindowHandle>,
+) -> Result<Display, String> {
+    let preference = gl_api_preference(raw_window_handle);
+    unsafe { Display::new(raw_display_handle, preference) }.map_err(|err| err.to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn gl_api_preference(_raw_window_handle: Option<RawWindowHandle>) -> DisplayApiPreference {
+    DisplayApiPreference::Cgl
+}
+
+#[cfg(windows)]
+fn gl_api_preference(raw_window_handle: Option<RawWindowHandle>) -> DisplayApiPreference {
+    DisplayApiPreference::Wgl(raw_window_handle)
+}
+
+// On Wayland there's no GLX to fall back to, so EGL is the only option. On X11, prefer GLX (with
+// an EGL fallback for drivers that only expose that) to match what the glutin-winit ecosystem
+// defaults to; with neither windowing feature enabled, fall back to EGL outright.
+#[cfg(all(unix, not(target_os = "macos"), feature = "wayland", not(feature = "x11")))]
+fn gl_api_preference(_raw_window_handle: Option<RawWindowHandle>) -> DisplayApiPreference {
+    DisplayApiPreference::Egl
+}
+
+#[cfg(all(unix, not(target_os = "macos"), feature = "x11"))]
+fn gl_api_preference(_raw_window_handle: Option<RawWindowHandle>) -> DisplayApiPreference {
+    DisplayApiPreference::GlxThenEgl(Box::new(winit::platform::x11::register_xlib_error_hook))
+}
+
+#[cfg(all(
+    unix,
+    not(target_os = "macos"),
+    not(feature = "x11"),
+    not(all(feature = "wayland", not(feature = "x11")))
+))]
+fn gl_api_preference(_raw_window_handle: Option<RawWindowHandle>) -> DisplayApiPreference {
+    DisplayApiPreference::Egl
+}
+
+/// Create the GL context every window uses, requesting an OpenGL 3.3 core profile on every
+/// platform.
+pub fn create_gl_context(
+    gl_display: &Display,
+    gl_config: &Config,
+    raw_window_handle: Option<RawWindowHandle>,
+) -> Result<NotCurrentContext, String> {
+    let context_attributes = ContextAttributesBuilder::new()
+        .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
+        .build(raw_window_handle);
+
+    unsafe { gl_display.create_context(gl_config, &context_attributes) }
+        .map_err(|err| err.to_string())
+}
+
+pub fn pick_gl_config(
+    gl_display: &Display,
+    raw_window_handle: Option<RawWindowHandle>,
+) -> Result<Config, String> {
+    let mut default_config = ConfigTemplateBuilder::new().with_transparency(true);
+
+    if let Some(raw_window_handle) = raw_window_handle {
+        default_config = default_config.compatible_with_native_window(raw_window_handle);
+    }
+
+    let configs = [default_config.clone()];
+
+    for config in configs {
+        let gl_config = unsafe {
+            gl_display.find_configs(config.build()).ok().and_then(|mut configs| configs.next())
+        };
+
+        if let Some(gl_config) = gl_config {
+            return Ok(gl_config);
+        }
+    }
+
+    Err(String::from("failed to find suitable GL configuration."))
+}
+
+pub fn create_gl_surface(
+    gl_context: &NotCurrentContext,
+    size: PhysicalSize<u32>,
+    raw_window_handle: RawWindowHandle,
+) -> Surface<WindowSurface> {
+    let gl_display = gl_context.display();
+    let gl_config = gl_context.config();
+
+    let surface_attributes =
+        SurfaceAttributesBuilder::<WindowSurface>::new().with_srgb(Some(false)).build(
+            raw_window_handle,
+            NonZeroU32::new(size.width).unwrap(),
+            NonZeroU32::new(size.height).unwrap(),
+        );
+
+    // Create the GL surface to draw into.
+    unsafe { gl_display.create_window_surface(&gl_config, &surface_attributes).unwrap() }
+}
@@ -3,8 +3,8 @@ use std::mem;
 use crate::display::{Rgb, SizeInfo};
 use crate::gl;
 use crate::gl::types::*;
-use crate::renderer::shader::{ShaderError, ShaderProgram};
-use crate::renderer::{self, cstr};
+use crate::renderer::shader::{GlslVersion, ShaderError, ShaderProgram};
+use crate::renderer::{self, cstr, hot_reload};
 
 #[derive(Debug, Copy, Clone)]
 pub struct RenderRect {
@@ -28,12 +28,23 @@ impl RenderRect {
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum RectKind {
     Normal = 0,
-    NumKinds = 1,
+    Undercurl = 1,
+    DottedUnderline = 2,
+    DashedUnderline = 3,
+    NumKinds = 4,
 }
 
 /// Shader sources for rect rendering program.
-static RECT_SHADER_F: &str = include_str!("../../res/rect.f.glsl");
 static RECT_SHADER_V: &str = include_str!("../../res/rect.v.glsl");
+static RECT_SHADER_F: &str = include_str!("../../res/rect.f.glsl");
+static UNDERCURL_SHADER_F: &str = include_str!("../../res/undercurl.f.glsl");
+static DOTTED_SHADER_F: &str = include_str!("../../res/dotted.f.glsl");
+static DASHED_SHADER_F: &str = include_str!("../../res/dashed.f.glsl");
+
+/// GLES2-dialect rect shaders, used when the context doesn't support core-profile GL 3.3. Only
+/// the solid-fill style is ported; underline styles fall back to it on this path.
+static GLES2_RECT_SHADER_V: &str = include_str!("../../res/gles2/rect.v.glsl");
+static GLES2_RECT_SHADER_F: &str = include_str!("../../res/gles2/rect.f.glsl");
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -55,68 +66,148 @@ pub struct RectRenderer {
     vao: GLuint,
     vbo: GLuint,
 
-    programs: [RectShaderProgram; 1],
+    programs: [RectShaderProgram; 4],
     vertices: [Vec<Vertex>; 4],
+
+    /// `true` on a GLES2 context, which has no VAOs: attribute bindings must be re-applied with
+    /// `glVertexAttribPointer`/`glEnableVertexAttribArray` on every [`Self::draw`] instead of
+    /// being cached once in a VAO at construction.
+    gles2: bool,
+
+    /// Watches `res/{rect,undercurl,dotted,dashed}.*.glsl` for changes under the
+    /// `live-shader-reload` feature; `None` when the feature is off.
+    watcher: Option<hot_reload::ShaderWatcher>,
 }
 
 impl RectRenderer {
-    pub fn new() -> Result<Self, renderer::Error> {
+    /// Build the renderer, compiling the GLES2-dialect shaders instead of the default
+    /// core-profile ones when `gles2` is set, as picked by the render thread based on the
+    /// context's reported GL version.
+    pub fn new(gles2: bool) -> Result<Self, renderer::Error> {
         let mut vao: GLuint = 0;
         let mut vbo: GLuint = 0;
 
-        let rect_program = RectShaderProgram::new()?;
+        let programs = if gles2 {
+            [
+                RectShaderProgram::new_gles2()?,
+                RectShaderProgram::new_gles2()?,
+                RectShaderProgram::new_gles2()?,
+                RectShaderProgram::new_gles2()?,
+            ]
+        } else {
+            [
+                RectShaderProgram::new(RECT_SHADER_F)?,
+                RectShaderProgram::new(UNDERCURL_SHADER_F)?,
+                RectShaderProgram::new(DOTTED_SHADER_F)?,
+                RectShaderProgram::new(DASHED_SHADER_F)?,
+            ]
+        };
 
         unsafe {
-            // Allocate buffers.
-            gl::GenVertexArrays(1, &mut vao);
             gl::GenBuffers(1, &mut vbo);
 
-            gl::BindVertexArray(vao);
+            if gles2 {
+                // No VAO support; attributes are bound fresh on every `draw` instead.
+                gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+                gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            } else {
+                gl::GenVertexArrays(1, &mut vao);
+                gl::BindVertexArray(vao);
+
+                // VBO binding is not part of VAO itself, but VBO binding is stored in attributes.
+                gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+
+                let mut attribute_offset = 0;
+
+                // Position.
+                gl::VertexAttribPointer(
+                    0,
+                    2,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    mem::size_of::<Vertex>() as i32,
+                    attribute_offset as *const _,
+                );
+                gl::EnableVertexAttribArray(0);
+                attribute_offset += mem::size_of::<f32>() * 2;
+
+                // Color.
+                gl::VertexAttribPointer(
+                    1,
+                    4,
+                    gl::UNSIGNED_BYTE,
+                    gl::TRUE,
+                    mem::size_of::<Vertex>() as i32,
+                    attribute_offset as *const _,
+                );
+                gl::EnableVertexAttribArray(1);
 
-            // VBO binding is not part of VAO itself, but VBO binding is stored in attributes.
-            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+                // Reset buffer bindings.
+                gl::BindVertexArray(0);
+                gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            }
+        }
 
-            let mut attribute_offset = 0;
+        let watcher = hot_reload::ShaderWatcher::new(&[
+            hot_reload::res_path("rect.v.glsl"),
+            hot_reload::res_path("rect.f.glsl"),
+            hot_reload::res_path("undercurl.f.glsl"),
+            hot_reload::res_path("dotted.f.glsl"),
+            hot_reload::res_path("dashed.f.glsl"),
+        ]);
 
-            // Position.
-            gl::VertexAttribPointer(
-                0,
-                2,
-                gl::FLOAT,
-                gl::FALSE,
-                mem::size_of::<Vertex>() as i32,
-                attribute_offset as *const _,
-            );
-            gl::EnableVertexAttribArray(0);
-            attribute_offset += mem::size_of::<f32>() * 2;
+        Ok(Self { vao, vbo, programs, vertices: Default::default(), gles2, watcher })
+    }
 
-            // Color.
-            gl::VertexAttribPointer(
-                1,
-                4,
-                gl::UNSIGNED_BYTE,
-                gl::TRUE,
-                mem::size_of::<Vertex>() as i32,
-                attribute_offset as *const _,
-            );
-            gl::EnableVertexAttribArray(1);
+    /// Called once per frame by the render thread: if a watched shader changed on disk, recompile
+    /// every
+    /// `RectKind` program and swap it in, keeping the previously working program on failure
+    /// instead of leaving the renderer without one.
+    pub fn reload_shaders_if_changed(&mut self) {
+        let Some(watcher) = &self.watcher else { return };
+        if !watcher.poll_changed() {
+            return;
+        }
 
-            // Reset buffer bindings.
-            gl::BindVertexArray(0);
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        if self.gles2 {
+            // The GLES2 fallback shaders aren't part of the live-reload loop.
+            return;
         }
 
-        let programs = [rect_program];
-        Ok(Self { vao, vbo, programs, vertices: Default::default() })
+        let Some(vertex) = hot_reload::read_source(&hot_reload::res_path("rect.v.glsl")) else {
+            return;
+        };
+
+        let fragments = [
+            hot_reload::res_path("rect.f.glsl"),
+            hot_reload::res_path("undercurl.f.glsl"),
+            hot_reload::res_path("dotted.f.glsl"),
+            hot_reload::res_path("dashed.f.glsl"),
+        ];
+
+        for (kind, fragment_path) in fragments.iter().enumerate() {
+            let Some(fragment) = hot_reload::read_source(fragment_path) else { continue };
+            match RectShaderProgram::with_sources(vertex, fragment) {
+                Ok(program) => self.programs[kind] = program,
+                Err(_) => println!(
+                    "failed to recompile {}, keeping previous program",
+                    fragment_path.display()
+                ),
+            }
+        }
     }
 
     pub fn draw(&mut self, size_info: &SizeInfo, rects: Vec<RenderRect>) {
         unsafe {
-            // Bind VAO to enable vertex attribute slots.
-            gl::BindVertexArray(self.vao);
-
-            // Bind VBO only once for buffer data upload only.
-            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            if self.gles2 {
+                gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            } else {
+                // Bind VAO to enable vertex attribute slots.
+                gl::BindVertexArray(self.vao);
+
+                // Bind VBO only once for buffer data upload only.
+                gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            }
         }
 
         let half_width = size_info.width() / 2.;
@@ -125,7 +216,9 @@ impl RectRenderer {
         // Build rect vertices vector.
         self.vertices.iter_mut().for_each(|vertices| vertices.clear());
         for rect in &rects {
-            Self::add_rect(&mut self.vertices[rect.kind as usize], half_width, half_height, rect);
+            // GLES2 only has the solid-fill shader, so every kind draws as a plain rect there.
+            let kind = if self.gles2 { RectKind::Normal } else { rect.kind };
+            Self::add_rect(&mut self.vertices[kind as usize], half_width, half_height, rect);
         }
 
         unsafe {
@@ -149,6 +242,10 @@ impl RectRenderer {
                     gl::STREAM_DRAW,
                 );
 
+                if self.gles2 {
+                    program.bind_gles2_attributes();
+                }
+
                 // Draw all vertices as list of triangles.
                 gl::DrawArrays(gl::TRIANGLES, 0, vertices.len() as i32);
             }
@@ -158,7 +255,9 @@ impl RectRenderer {
 
             // Reset buffer bindings to nothing.
             gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
+            if !self.gles2 {
+                gl::BindVertexArray(0);
+            }
         }
     }
 
@@ -216,25 +315,89 @@ pub struct RectShaderProgram {
 
     /// A padding from the bottom of the screen to viewport.
     u_padding_y: Option<GLint>,
+
+    /// Attribute locations, resolved for every program but only used on the GLES2 path, since
+    /// the GL3 path caches attribute bindings in a VAO instead.
+    a_position: GLint,
+    a_color: GLint,
 }
 
 impl RectShaderProgram {
-    pub fn new() -> Result<Self, ShaderError> {
-        let program = ShaderProgram::new(None, RECT_SHADER_V, RECT_SHADER_F)?;
+    /// Build the program for one [`RectKind`], pairing the shared vertex shader with the
+    /// fragment shader that draws that kind's style (solid fill, undercurl wave, dotted or
+    /// dashed underline).
+    pub fn new(fragment_shader: &'static str) -> Result<Self, ShaderError> {
+        let program = ShaderProgram::new(None, RECT_SHADER_V, fragment_shader)?;
+        Ok(Self::from_program(program))
+    }
+
+    /// Like [`Self::new`], but with both shader sources supplied explicitly -- used to recompile
+    /// from freshly re-read disk sources during live reload.
+    pub fn with_sources(
+        vertex_shader: &'static str,
+        fragment_shader: &'static str,
+    ) -> Result<Self, ShaderError> {
+        let program = ShaderProgram::new(None, vertex_shader, fragment_shader)?;
+        Ok(Self::from_program(program))
+    }
 
-        Ok(Self {
+    /// Build the GLES2-dialect solid-fill program, used as the fallback for every [`RectKind`]
+    /// when the context doesn't support core-profile GL 3.3.
+    pub fn new_gles2() -> Result<Self, ShaderError> {
+        let program = ShaderProgram::with_version(
+            GlslVersion::Gles100,
+            None,
+            GLES2_RECT_SHADER_V,
+            GLES2_RECT_SHADER_F,
+        )?;
+        Ok(Self::from_program(program))
+    }
+
+    fn from_program(program: ShaderProgram) -> Self {
+        Self {
             u_cell_width: program.get_uniform_location(cstr!("cellWidth")).ok(),
             u_cell_height: program.get_uniform_location(cstr!("cellHeight")).ok(),
             u_padding_x: program.get_uniform_location(cstr!("paddingX")).ok(),
             u_padding_y: program.get_uniform_location(cstr!("paddingY")).ok(),
+            a_position: unsafe { gl::GetAttribLocation(program.id(), cstr!("aPosition").as_ptr()) },
+            a_color: unsafe { gl::GetAttribLocation(program.id(), cstr!("aColor").as_ptr()) },
             program,
-        })
+        }
     }
 
     fn id(&self) -> GLuint {
         self.program.id()
     }
 
+    /// Re-apply the vertex attribute bindings for the currently-bound VBO. Only needed on GLES2,
+    /// which has no VAOs to cache this state in.
+    fn bind_gles2_attributes(&self) {
+        unsafe {
+            let mut offset = 0;
+
+            gl::VertexAttribPointer(
+                self.a_position as GLuint,
+                2,
+                gl::FLOAT,
+                gl::FALSE,
+                mem::size_of::<Vertex>() as i32,
+                offset as *const _,
+            );
+            gl::EnableVertexAttribArray(self.a_position as GLuint);
+            offset += mem::size_of::<f32>() * 2;
+
+            gl::VertexAttribPointer(
+                self.a_color as GLuint,
+                4,
+                gl::UNSIGNED_BYTE,
+                gl::TRUE,
+                mem::size_of::<Vertex>() as i32,
+                offset as *const _,
+            );
+            gl::EnableVertexAttribArray(self.a_color as GLuint);
+        }
+    }
+
     pub fn update_uniforms(&self, size_info: &SizeInfo) {
         let viewport_height = size_info.height() - size_info.padding_y();
         let padding_y = viewport_height
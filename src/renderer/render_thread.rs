@@ -0,0 +1,393 @@
+//! A dedicated thread that owns the GL context/surface and all per-frame rendering state, so
+//! rasterizing glyphs and swapping buffers never blocks the winit event loop thread.
+
+use std::num::NonZeroU32;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+use winit::dpi::PhysicalSize;
+
+use glutin::config::Config as GlConfig;
+use glutin::context::{NotCurrentContext, PossiblyCurrentContext};
+use glutin::display::{Display as GlDisplay, GetGlDisplay, GlDisplay as GlDisplayExt};
+use glutin::prelude::*;
+use glutin::surface::{Rect, Surface, WindowSurface};
+
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+use crossfont::{FontDesc, FontKey, Rasterizer, Size};
+
+use crate::display::window::Window;
+use crate::display::SizeInfo;
+use crate::editor::Editor;
+use crate::renderer::debug_overlay::{DebugOverlay, FrameStats};
+use crate::renderer::rects::RectRenderer;
+use crate::renderer::{self, TextRenderer};
+
+/// How many consecutive dropped frames (failed `swap_buffers` calls) the render
+/// thread tolerates before giving up on recovery and panicking. Guards against spinning forever
+/// recreating a context that can never come back (e.g. the GPU is gone for good).
+const MAX_CONSECUTIVE_RESETS: u32 = 3;
+
+/// Cell size used when the rasterizer can't report metrics for the loaded font, so a broken font
+/// still produces a usable (if misjudged) grid instead of taking down the render thread.
+const DEFAULT_CELL_WIDTH: f32 = 10.;
+const DEFAULT_CELL_HEIGHT: f32 = 20.;
+
+/// Messages the event-loop thread sends to a window's render thread.
+enum RenderMessage {
+    Resize(PhysicalSize<u32>),
+    ScaleFactorChanged(f64),
+    ToggleDebugOverlay,
+    Redraw,
+}
+
+/// Handle the event-loop thread holds to a window's render thread. Dropping it asks the thread
+/// to exit and waits for it to finish, so the GL context is torn down on the thread that made it
+/// current.
+pub struct RenderThread {
+    sender: Sender<RenderMessage>,
+    join_handle: Option<JoinHandle<()>>,
+
+    /// Whether the render thread has presented at least one frame. Mirrors the
+    /// `Window::has_frame` flag the windowing layer already uses for the same purpose, just
+    /// scoped to this thread's own frame pacing instead of the platform window's.
+    has_frame: Arc<AtomicBool>,
+
+    /// The most recently computed `SizeInfo`, updated by the render thread after every resize or
+    /// scale-factor change so the event-loop thread can read it without blocking on a message
+    /// round-trip.
+    size_info: Arc<Mutex<SizeInfo>>,
+}
+
+impl RenderThread {
+    /// Spawn a render thread that takes `gl_context` current on itself and owns everything
+    /// needed to rasterize and present frames for `window`.
+    ///
+    /// `gl_display` and `gl_config` are kept around (not just `gl_context`) so a lost context can
+    /// be recreated from scratch without going back to the event-loop thread.
+    pub fn spawn(
+        window: &Window,
+        gl_display: GlDisplay,
+        gl_config: GlConfig,
+        gl_context: NotCurrentContext,
+    ) -> RenderThread {
+        let (sender, receiver) = mpsc::channel();
+
+        let raw_window_handle = window.raw_window_handle();
+        let initial_size = window.inner_size();
+        let scale_factor = window.scale_factor;
+
+        let has_frame = Arc::new(AtomicBool::new(false));
+        let size_info = Arc::new(Mutex::new(SizeInfo::new(
+            initial_size.width as f32,
+            initial_size.height as f32,
+            1.,
+            1.,
+            5. * scale_factor as f32,
+            5. * scale_factor as f32,
+        )));
+
+        let thread_has_frame = Arc::clone(&has_frame);
+        let thread_size_info = Arc::clone(&size_info);
+
+        let join_handle = std::thread::Builder::new()
+            .name("render".to_owned())
+            .spawn(move || {
+                render_thread_main(
+                    gl_display,
+                    gl_config,
+                    gl_context,
+                    raw_window_handle,
+                    initial_size,
+                    scale_factor,
+                    thread_has_frame,
+                    thread_size_info,
+                    receiver,
+                )
+            })
+            .expect("failed to spawn render thread");
+
+        RenderThread { sender, join_handle: Some(join_handle), has_frame, size_info }
+    }
+
+    /// The most recently computed cell/window geometry.
+    pub fn size_info(&self) -> SizeInfo {
+        *self.size_info.lock().unwrap()
+    }
+
+    /// Whether a frame has ever been presented, mirroring `Window::has_frame`.
+    pub fn has_frame(&self) -> bool {
+        self.has_frame.load(Ordering::Acquire)
+    }
+
+    pub fn resize(&self, new_size: PhysicalSize<u32>) {
+        let _ = self.sender.send(RenderMessage::Resize(new_size));
+    }
+
+    pub fn set_scale_factor(&self, scale_factor: f64) {
+        let _ = self.sender.send(RenderMessage::ScaleFactorChanged(scale_factor));
+    }
+
+    pub fn toggle_debug_overlay(&self) {
+        let _ = self.sender.send(RenderMessage::ToggleDebugOverlay);
+    }
+
+    /// Ask the render thread to draw and present a frame. Returns immediately; the actual
+    /// rasterization and `swap_buffers` happen on the render thread.
+    pub fn request_redraw(&self) {
+        let _ = self.sender.send(RenderMessage::Redraw);
+    }
+}
+
+impl Drop for RenderThread {
+    fn drop(&mut self) {
+        // Dropping `sender` closes the channel, which ends the render thread's `for message in
+        // receiver` loop.
+        let (closed_sender, _) = mpsc::channel();
+        drop(std::mem::replace(&mut self.sender, closed_sender));
+        if let Some(join_handle) = self.join_handle.take() {
+            let _ = join_handle.join();
+        }
+    }
+}
+
+/// Everything GPU-resident that's lost along with the GL context itself, so a context reset can
+/// rebuild exactly this and nothing more.
+struct GraphicsState {
+    surface: Surface<WindowSurface>,
+    context: PossiblyCurrentContext,
+    renderer: TextRenderer,
+    rect_renderer: RectRenderer,
+}
+
+impl GraphicsState {
+    /// Create a new GL context against `gl_config`, make it current on this thread, and build the
+    /// renderers it backs.
+    fn new(
+        gl_display: &GlDisplay,
+        gl_config: &GlConfig,
+        raw_window_handle: RawWindowHandle,
+        size: PhysicalSize<u32>,
+        rasterizer: &mut Rasterizer,
+    ) -> Self {
+        #[cfg(not(windows))]
+        let context_window_handle = None;
+
+        let gl_context =
+            renderer::platform::create_gl_context(gl_display, gl_config, context_window_handle)
+                .expect("failed to create GL context");
+        let surface = renderer::platform::create_gl_surface(&gl_context, size, raw_window_handle);
+        let context = gl_context.make_current(&surface).expect("failed to make context current");
+
+        // Same GL-version check `TextRenderer::new` makes internally, so the rect renderer picks
+        // the matching dialect.
+        let gles2 = context.display().version_string().contains("OpenGL ES");
+
+        let renderer =
+            TextRenderer::new(&context, rasterizer).expect("failed to initialize text renderer");
+        let rect_renderer = RectRenderer::new(gles2).expect("failed to initialize rect renderer");
+
+        Self { surface, context, renderer, rect_renderer }
+    }
+}
+
+/// Body of the spawned render thread: make a GL context current here, build every piece of
+/// rendering state that used to live on `Display`, then loop on `receiver` until the channel
+/// closes.
+fn render_thread_main(
+    gl_display: GlDisplay,
+    gl_config: GlConfig,
+    gl_context: NotCurrentContext,
+    raw_window_handle: RawWindowHandle,
+    initial_size: PhysicalSize<u32>,
+    scale_factor: f64,
+    has_frame: Arc<AtomicBool>,
+    shared_size_info: Arc<Mutex<SizeInfo>>,
+    receiver: mpsc::Receiver<RenderMessage>,
+) {
+    let mut rasterizer = Rasterizer::new(scale_factor as f32);
+
+    let font_name = String::from("Source Code Pro");
+    let font_size = Size::new(16.);
+    let regular_desc = FontDesc::new(&font_name, &String::from("Regular"));
+    let mut font_key = rasterizer.load_font(&regular_desc, font_size).unwrap();
+
+    let offset_x = 1_f64;
+    let offset_y = 2_f64;
+    let (cell_width, cell_height) = match rasterizer.metrics(font_key, font_size) {
+        Ok(metrics) => (
+            (metrics.average_advance + offset_x).floor().max(1.) as f32,
+            (metrics.line_height + offset_y).floor().max(1.) as f32,
+        ),
+        Err(err) => {
+            println!("failed to get font metrics, falling back to default cell size: {err}");
+            (DEFAULT_CELL_WIDTH, DEFAULT_CELL_HEIGHT)
+        },
+    };
+
+    // The caller already built a `NotCurrentContext` for the first frame; reuse it instead of
+    // creating a second one. Later resets build fresh state through `GraphicsState::new`.
+    let surface =
+        renderer::platform::create_gl_surface(&gl_context, initial_size, raw_window_handle);
+    let context = gl_context.make_current(&surface).expect("failed to make context current");
+    let gles2 = context.display().version_string().contains("OpenGL ES");
+    let renderer =
+        TextRenderer::new(&context, &mut rasterizer).expect("failed to initialize text renderer");
+    let rect_renderer = RectRenderer::new(gles2).expect("failed to initialize rect renderer");
+    let mut graphics = GraphicsState { surface, context, renderer, rect_renderer };
+
+    let mut debug_overlay = DebugOverlay::new();
+    let mut editor = Editor::default();
+    let mut last_frame_ms = 0_f32;
+    let mut consecutive_resets = 0_u32;
+
+    let mut size_info = SizeInfo::new(
+        initial_size.width as f32,
+        initial_size.height as f32,
+        cell_width,
+        cell_height,
+        5. * scale_factor as f32,
+        5. * scale_factor as f32,
+    );
+    *shared_size_info.lock().unwrap() = size_info;
+
+    for message in receiver {
+        match message {
+            RenderMessage::Resize(new_size) => {
+                let (width, height) =
+                    match (NonZeroU32::new(new_size.width), NonZeroU32::new(new_size.height)) {
+                        (Some(width), Some(height)) => (width, height),
+                        // A minimized window reports a zero-sized inner area; nothing to draw
+                        // until it's restored.
+                        _ => continue,
+                    };
+
+                graphics.surface.resize(&graphics.context, width, height);
+
+                size_info = SizeInfo::new(
+                    new_size.width as f32,
+                    new_size.height as f32,
+                    size_info.cell_width,
+                    size_info.cell_height,
+                    size_info.padding_x,
+                    size_info.padding_y,
+                );
+                *shared_size_info.lock().unwrap() = size_info;
+
+                graphics.renderer.resize(&size_info);
+            },
+            RenderMessage::ScaleFactorChanged(scale_factor) => {
+                rasterizer.update_dpr(scale_factor as f32);
+
+                let regular_desc = FontDesc::new(&font_name, &String::from("Regular"));
+                font_key = rasterizer.load_font(&regular_desc, font_size).unwrap();
+
+                let (cell_width, cell_height) = match rasterizer.metrics(font_key, font_size) {
+                    Ok(metrics) => (
+                        (metrics.average_advance + offset_x).floor().max(1.) as f32,
+                        (metrics.line_height + offset_y).floor().max(1.) as f32,
+                    ),
+                    // Keep whatever cell size the grid already has rather than snapping to the
+                    // unrelated global default, which would visibly misjudge every cell's
+                    // position for a font that was working fine a moment ago.
+                    Err(err) => {
+                        println!("failed to get font metrics, keeping previous cell size: {err}");
+                        (size_info.cell_width, size_info.cell_height)
+                    },
+                };
+
+                size_info = SizeInfo::new(
+                    size_info.width,
+                    size_info.height,
+                    cell_width,
+                    cell_height,
+                    5. * (scale_factor as f32),
+                    5. * (scale_factor as f32),
+                );
+                *shared_size_info.lock().unwrap() = size_info;
+
+                graphics.renderer.clear_atlas();
+                graphics.renderer.resize(&size_info);
+            },
+            RenderMessage::ToggleDebugOverlay => debug_overlay.toggle(),
+            RenderMessage::Redraw => {
+                let frame_start = Instant::now();
+
+                graphics.renderer.reload_shaders_if_changed();
+                graphics.rect_renderer.reload_shaders_if_changed();
+
+                let rasterize_start = Instant::now();
+                graphics.renderer.draw_cells(&size_info, &mut rasterizer, font_key, font_size);
+                let rasterize_ms = rasterize_start.elapsed().as_secs_f32() * 1000.;
+
+                // TODO: feed `cells` into `graphics.renderer.draw_cells` once that path accepts
+                // anything other than its own hardcoded demo content; for now only the cursor and
+                // the damage it (along with the rest of the frame) produces are wired up.
+                let (_cells, cursor, damage) = editor.buffer_mut().get_renderables(&size_info);
+                if editor.buffer().cursor_visible() {
+                    graphics.rect_renderer.draw(&size_info, cursor.rects(&size_info, 0.15));
+                }
+
+                let stats = FrameStats {
+                    rasterize_ms,
+                    frame_ms: last_frame_ms,
+                    // Draw calls are not counted; a fixed 1 is reported.
+                    draw_calls: 1,
+                    atlas_glyph_count: graphics.renderer.atlas_glyph_count(),
+                    atlas_occupancy: graphics.renderer.atlas_occupancy(),
+                };
+                debug_overlay.draw(&size_info, &mut graphics.rect_renderer, &stats);
+
+                // An empty damage list means nothing changed since the last frame (no new cells,
+                // no cursor move); there's nothing to tell the compositor about, so fall back to
+                // swapping the whole surface rather than handing the platform a zero-rect list.
+                let damage_rects: Vec<Rect> = damage
+                    .iter()
+                    .map(|rect| Rect::new(rect.x, rect.y, rect.width, rect.height))
+                    .collect();
+
+                let swap_result = if damage_rects.is_empty() {
+                    graphics.surface.deref().swap_buffers(&graphics.context)
+                } else {
+                    graphics
+                        .surface
+                        .deref()
+                        .swap_buffers_with_damage(&graphics.context, &damage_rects)
+                };
+
+                if swap_result.is_err() {
+                    // The GPU driver reset the context underneath us (crash, suspend/resume, an
+                    // external display getting unplugged mid-frame). Rebuild everything
+                    // GL-resident from the still-valid `gl_display`/`gl_config` and retry once
+                    // the new context is current; glyphs are reuploaded to the fresh (empty)
+                    // atlas lazily as `draw_cells` re-rasterizes them.
+                    consecutive_resets += 1;
+                    assert!(
+                        consecutive_resets <= MAX_CONSECUTIVE_RESETS,
+                        "GL context repeatedly lost; giving up after {consecutive_resets} resets"
+                    );
+
+                    graphics = GraphicsState::new(
+                        &gl_display,
+                        &gl_config,
+                        raw_window_handle,
+                        PhysicalSize::new(size_info.width as u32, size_info.height as u32),
+                        &mut rasterizer,
+                    );
+                    graphics.renderer.resize(&size_info);
+                } else {
+                    consecutive_resets = 0;
+                }
+
+                has_frame.store(true, Ordering::Release);
+                last_frame_ms = frame_start.elapsed().as_secs_f32() * 1000.;
+            },
+        }
+    }
+}
+
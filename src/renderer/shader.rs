@@ -13,8 +13,21 @@ impl ShaderProgram {
         vertex_shader: &'static str,
         fragment_shader: &'static str,
     ) -> Self {
-        let vertex_shader = Shader::new(shader_header, gl::VERTEX_SHADER, vertex_shader);
-        let fragment_shader = Shader::new(shader_header, gl::FRAGMENT_SHADER, fragment_shader);
+        Self::with_version(GlslVersion::Gl330, shader_header, vertex_shader, fragment_shader)
+    }
+
+    /// Like [`Self::new`], but targeting a specific GLSL dialect. Used to compile the GLES2
+    /// fallback variants, whose sources start from `#version 100` instead of core-profile
+    /// `#version 330 core`.
+    pub fn with_version(
+        version: GlslVersion,
+        shader_header: Option<&str>,
+        vertex_shader: &'static str,
+        fragment_shader: &'static str,
+    ) -> Self {
+        let vertex_shader = Shader::new(version, shader_header, gl::VERTEX_SHADER, vertex_shader);
+        let fragment_shader =
+            Shader::new(version, shader_header, gl::FRAGMENT_SHADER, fragment_shader);
 
         let program = unsafe { Self(gl::CreateProgram()) };
 
@@ -48,13 +61,31 @@ impl Drop for ShaderProgram {
     }
 }
 
+/// Which GLSL dialect a shader's `#version` header targets.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GlslVersion {
+    /// Core-profile desktop GL 3.3, used on the default `Glsl3Renderer` path.
+    Gl330,
+    /// GLSL ES 1.00 (`attribute`/`varying`, `gl_FragColor`), used on the GLES2 fallback path.
+    Gles100,
+}
+
+impl GlslVersion {
+    fn header(self) -> &'static str {
+        match self {
+            GlslVersion::Gl330 => "#version 330 core\n",
+            GlslVersion::Gles100 => "#version 100\n",
+        }
+    }
+}
+
 /// A wrapper for a shader id, with automatic lifetime management.
 #[derive(Debug)]
 struct Shader(GLuint);
 
 impl Shader {
-    fn new(shader_header: Option<&str>, kind: GLenum, source: &'static str) -> Self {
-        let version_header = "#version 330 core\n";
+    fn new(version: GlslVersion, shader_header: Option<&str>, kind: GLenum, source: &'static str) -> Self {
+        let version_header = version.header();
         let mut sources = Vec::<*const GLchar>::with_capacity(3);
         let mut lengthes = Vec::<GLint>::with_capacity(3);
         sources.push(version_header.as_ptr().cast());
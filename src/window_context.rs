@@ -1,40 +1,36 @@
-//! Terminal window context.
+//! Editor window context.
 
 use std::error::Error;
-use std::mem;
-use std::sync::atomic::Ordering;
 
-use crossfont::Size;
-use glutin::config::GetGlConfig;
 use glutin::context::NotCurrentContext;
-use glutin::display::GetGlDisplay;
+use glutin::display::Display as GlDisplay;
 use raw_window_handle::HasRawDisplayHandle;
-use winit::event::{Event as WinitEvent, Modifiers};
+use winit::event::{Modifiers, WindowEvent};
 use winit::event_loop::{EventLoopProxy, EventLoopWindowTarget};
 use winit::window::WindowId;
 
+use glutin::config::Config as GlConfig;
+
 use crate::display::window::Window;
-use crate::display::Display;
+use crate::display::{Display, SizeInfo};
 use crate::editor::Editor;
-use crate::event::{ActionContext, Event};
-use crate::{input, renderer};
-
-use crossfont::Size as FontSize;
+use crate::event::Event;
+use crate::input::{self, ActionContext};
+use crate::renderer;
 
-/// Event context for one individual Alacritty window.
+/// Event context for one individual editor window.
 pub struct WindowContext {
     pub display: Display,
-    event_queue: Vec<WinitEvent<'static, Event>>,
     editor: Editor,
     modifiers: Modifiers,
-    font_size: Size,
-    dirty: bool,
-    occluded: bool,
 }
 
 impl WindowContext {
-    /// Create initial window context that dous bootstrapping the graphics Api we're going to use.
-    pub fn initial(event_loop: &EventLoopWindowTarget<Event>) -> Result<Self, Box<dyn Error>> {
+    /// Create a window context, bootstrapping the graphics API it's going to use.
+    pub fn initial(
+        event_loop: &EventLoopWindowTarget<Event>,
+        background_opacity: f32,
+    ) -> Result<Self, Box<dyn Error>> {
         let raw_display_handle = event_loop.raw_display_handle();
 
         #[cfg(not(windows))]
@@ -44,121 +40,100 @@ impl WindowContext {
             renderer::platform::create_gl_display(raw_display_handle, raw_window_handle)?;
         let gl_config = renderer::platform::pick_gl_config(&gl_display, raw_window_handle)?;
 
-        #[cfg(not(windows))]
-        let window = Window::new(event_loop)?;
+        let window = Window::new(event_loop, background_opacity)?;
 
-        // Create context.
         let gl_context =
             renderer::platform::create_gl_context(&gl_display, &gl_config, raw_window_handle)?;
 
-        Self::new(window, gl_context)
+        Ok(Self::new(window, gl_display, gl_config, gl_context))
     }
 
-    /// Create additional context with the graphics platform other windows are using.
-    pub fn additional(
-        &self,
-        event_loop: &EventLoopWindowTarget<Event>,
-    ) -> Result<Self, Box<dyn Error>> {
-        // Get any window and take its GL config and display to build a new context.
-        let (gl_display, gl_config) = {
-            let gl_context = self.display.gl_context();
-            (gl_context.display(), gl_context.config())
-        };
-
-        let window = Window::new(event_loop)?;
-
-        // Create context.
-        let raw_window_handle = window.raw_window_handle();
-        let gl_context = renderer::platform::create_gl_context(
-            &gl_display,
-            &gl_config,
-            Some(raw_window_handle),
-        )?;
-
-        Self::new(window, gl_context)
-    }
+    /// Create a new editor window context.
+    fn new(
+        window: Window,
+        gl_display: GlDisplay,
+        gl_config: GlConfig,
+        gl_context: NotCurrentContext,
+    ) -> Self {
+        let display = Display::new(window, gl_display, gl_config, gl_context);
 
-    /// Create a new terminal window context.
-    fn new(window: Window, context: NotCurrentContext) -> Result<Self, Box<dyn Error>> {
-        // Create a display.
-        //
-        // The display manages a window and can draw the terminal.
-        let display = Display::new(window, context)?;
-
-        let font_size = FontSize::new(16.);
-
-        // Create context for the Alacritty window.
-        Ok(WindowContext {
-            font_size,
-            editor: Default::default(),
-            display,
-            event_queue: Default::default(),
-            modifiers: Default::default(),
-            dirty: Default::default(),
-            occluded: Default::default(),
-        })
+        WindowContext { display, editor: Editor::default(), modifiers: Modifiers::default() }
     }
 
-    /// Process events for this terminal window.
-    pub fn handle_event(
-        &mut self,
-        event_proxy: &EventLoopProxy<Event>,
-        event: WinitEvent<'_, Event>,
-    ) {
+    /// Handle a window-specific event routed to this context by `event::Processor::run`.
+    pub fn handle_event(&mut self, event_proxy: &EventLoopProxy<Event>, event: WindowEvent) {
         match event {
-            // Skip further event handling with no staged updates.
-            WinitEvent::RedrawEventsCleared if self.event_queue.is_empty() && !self.dirty => {
-                return;
+            WindowEvent::Resized(size) => self.display.handle_resize(size),
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.display.handle_scale_factor_change(scale_factor)
             },
-            // Continue to process all pending events.
-            WinitEvent::RedrawEventsCleared => (),
-            // Transmute to extend lifetime, which exists only for `ScaleFactorChanged` event.
-            // Since we remap that event to remove the lifetime, this is safe.
-            event => unsafe {
-                self.event_queue.push(mem::transmute(event));
-                return;
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.input_processor(event_proxy).modifiers_input(modifiers)
             },
+            WindowEvent::KeyboardInput { event: key_event, .. } => {
+                self.input_processor(event_proxy).key_input(key_event)
+            },
+            _ => (),
         }
+    }
 
-        let context = ActionContext {
-            modifiers: &mut self.modifiers,
-            font_size: &mut self.font_size,
+    /// Build an [`input::Processor`] borrowing this window's editor state for a single event.
+    fn input_processor<'a>(
+        &'a mut self,
+        event_proxy: &'a EventLoopProxy<Event>,
+    ) -> input::Processor<Context<'a>> {
+        input::Processor::new(Context {
             display: &mut self.display,
-            dirty: &mut self.dirty,
-            occluded: &mut self.occluded,
             editor: &mut self.editor,
+            modifiers: &mut self.modifiers,
             event_proxy,
-        };
-        let mut processor = input::Processor::new(context);
+        })
+    }
 
-        for event in self.event_queue.drain(..) {
-            processor.handle_event(event);
-        }
+    /// ID of this editor window.
+    pub fn id(&self) -> WindowId {
+        self.display.window.id()
+    }
+}
 
-        // Process DisplayUpdate events.
-        if self.display.pending_update.dirty {
-            self.display.handle_update();
-            self.dirty = true;
-        }
+/// [`ActionContext`] borrowing one [`WindowContext`]'s state for the duration of a single event.
+struct Context<'a> {
+    display: &'a mut Display,
+    editor: &'a mut Editor,
+    modifiers: &'a mut Modifiers,
+    event_proxy: &'a EventLoopProxy<Event>,
+}
 
-        // Skip rendering until we get a new frame.
-        if !self.display.window.has_frame.load(Ordering::Relaxed) {
-            return;
-        }
+impl<'a> ActionContext for Context<'a> {
+    fn size_info(&self) -> SizeInfo {
+        self.display.size_info()
+    }
 
-        if self.dirty && !self.occluded {
-            // Force the display to process any pending display update.
-            self.display.process_renderer_update();
+    fn modifiers(&mut self) -> &mut Modifiers {
+        self.modifiers
+    }
 
-            self.dirty = false;
+    fn window(&mut self) -> &mut Window {
+        &mut self.display.window
+    }
 
-            // Redraw the window.
-            self.display.draw(&self.editor);
-        }
+    fn display(&mut self) -> &mut Display {
+        self.display
     }
 
-    /// ID of this terminal context.
-    pub fn id(&self) -> WindowId {
-        self.display.window.id()
+    fn editor(&self) -> &Editor {
+        self.editor
+    }
+
+    fn editor_mut(&mut self) -> &mut Editor {
+        self.editor
+    }
+
+    fn create_new_window(&mut self) {
+        let _ = self.event_proxy.send_event(Event::CreateWindow);
+    }
+
+    fn close_window(&mut self, window_id: WindowId) {
+        let _ = self.event_proxy.send_event(Event::CloseWindow(window_id));
     }
 }